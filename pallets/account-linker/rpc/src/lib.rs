@@ -0,0 +1,174 @@
+//! RPC interface for querying the links of the account linker pallet.
+//! ETH addresses are formatted as EIP-55 checksummed hex and BTC addresses as strings.
+//! Addresses of the chains of the runtime's link verifiers, e.g. Solana or Cosmos, are formatted
+//! as strings, with their chain id.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::hashing::keccak_256;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use account_linker_runtime_api::AccountLinkerApi as AccountLinkerRuntimeApi;
+use account_linker_runtime_api::{ChainId, ExternalAddress, LinkedAddresses};
+
+/// The addresses of every chain linked to an account
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct LinkedAccounts<AccountId> {
+	pub eth: Vec<String>,
+	pub btc: Vec<String>,
+	pub polkadot: Vec<AccountId>,
+	pub chains: Vec<(ChainId, Vec<String>)>,
+}
+
+/// An address of another chain, e.g. `{ "eth": "0x5aAe..." }`
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Address<AccountId> {
+	Eth(String),
+	Btc(String),
+	Polkadot(AccountId),
+	Chain(ChainId, String),
+}
+
+#[rpc]
+pub trait AccountLinkerApi<BlockHash, AccountId> {
+	/// The addresses of every chain linked to `account`
+	#[rpc(name = "accountLinker_linkedAccounts")]
+	fn linked_accounts(&self, account: AccountId, at: Option<BlockHash>) -> Result<LinkedAccounts<AccountId>>;
+
+	/// The accounts `address` is linked to
+	#[rpc(name = "accountLinker_ownerOf")]
+	fn owner_of(&self, address: Address<AccountId>, at: Option<BlockHash>) -> Result<Vec<AccountId>>;
+}
+
+/// Error code of a failed runtime API call
+const RUNTIME_ERROR: i64 = 1;
+
+/// Implementation of `AccountLinkerApi` on top of the account linker runtime API
+pub struct AccountLinker<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> AccountLinker<C, Block> {
+	pub fn new(client: Arc<C>) -> Self {
+		AccountLinker { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block, AccountId> AccountLinkerApi<<Block as BlockT>::Hash, AccountId> for AccountLinker<C, Block>
+where
+	Block: BlockT,
+	C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: AccountLinkerRuntimeApi<Block, AccountId>,
+	AccountId: Codec,
+{
+	fn linked_accounts(&self, account: AccountId, at: Option<<Block as BlockT>::Hash>) -> Result<LinkedAccounts<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let linked: LinkedAddresses<AccountId> = api.linked_accounts(&at, account).map_err(runtime_error)?;
+
+		Ok(LinkedAccounts {
+			eth: linked.eth.iter().map(eth_checksum_address).collect(),
+			btc: linked.btc.iter().map(|addr| String::from_utf8_lossy(addr).into_owned()).collect(),
+			polkadot: linked.polkadot,
+			chains: linked.chains.into_iter().map(|(chain_id, addrs)| (
+				chain_id,
+				addrs.iter().map(|addr| String::from_utf8_lossy(addr).into_owned()).collect(),
+			)).collect(),
+		})
+	}
+
+	fn owner_of(&self, address: Address<AccountId>, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let address = match address {
+			Address::Eth(addr) => ExternalAddress::Eth(parse_eth_address(&addr)?),
+			Address::Btc(addr) => ExternalAddress::Btc(addr.into_bytes()),
+			Address::Polkadot(account) => ExternalAddress::Polkadot(account),
+			Address::Chain(chain_id, addr) => ExternalAddress::Chain(chain_id, addr.into_bytes()),
+		};
+
+		api.owner_of(&at, address).map_err(runtime_error)
+	}
+}
+
+fn runtime_error(e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Unable to query the account linker.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+/// Format an eth address as EIP-55 checksummed hex, see https://eips.ethereum.org/EIPS/eip-55
+pub fn eth_checksum_address(addr: &[u8; 20]) -> String {
+	let lower = hex::encode(addr);
+	let hash = keccak_256(lower.as_bytes());
+
+	let checksummed: String = lower.chars().enumerate().map(|(i, c)| {
+		let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0x0f };
+		if nibble >= 8 { c.to_ascii_uppercase() } else { c }
+	}).collect();
+
+	format!("0x{}", checksummed)
+}
+
+/// Parse a hex eth address, the checksum is verified if the address is mixed-case
+fn parse_eth_address(addr: &str) -> Result<[u8; 20]> {
+	let hex_addr = addr.strip_prefix("0x").unwrap_or(addr);
+
+	let bytes = hex::decode(hex_addr).map_err(|_| RpcError::invalid_params("Invalid hex eth address."))?;
+	if bytes.len() != 20 {
+		return Err(RpcError::invalid_params("Eth address must be 20 bytes."));
+	}
+	let mut res = [0u8; 20];
+	res.copy_from_slice(&bytes);
+
+	let mixed_case = hex_addr.chars().any(|c| c.is_ascii_lowercase()) && hex_addr.chars().any(|c| c.is_ascii_uppercase());
+	if mixed_case && eth_checksum_address(&res)[2..] != *hex_addr {
+		return Err(RpcError::invalid_params("Invalid EIP-55 checksum of eth address."));
+	}
+
+	Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// test vectors of https://eips.ethereum.org/EIPS/eip-55
+	const CHECKSUMMED: [&str; 4] = [
+		"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+		"0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+		"0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+		"0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+	];
+
+	#[test]
+	fn checksum_address() {
+		for addr in CHECKSUMMED.iter() {
+			let parsed = parse_eth_address(&addr.to_lowercase()).unwrap();
+			assert_eq!(eth_checksum_address(&parsed), *addr);
+		}
+	}
+
+	#[test]
+	fn parse_checksummed_address() {
+		for addr in CHECKSUMMED.iter() {
+			assert!(parse_eth_address(addr).is_ok());
+			assert!(parse_eth_address(&addr.to_uppercase()[2..]).is_ok());
+		}
+		// the case of the first letter is swapped
+		assert!(parse_eth_address("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+		assert!(parse_eth_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA").is_err());
+	}
+}
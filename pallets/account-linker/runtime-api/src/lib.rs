@@ -0,0 +1,45 @@
+//! Runtime API definition for querying the links of the account linker pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// Identifies a chain whose addresses are linked with a `LinkVerifier` of the runtime,
+/// e.g. its SLIP-44 coin type
+pub type ChainId = u32;
+
+/// The addresses of every chain linked to an account
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct LinkedAddresses<AccountId> {
+	pub eth: Vec<[u8; 20]>,
+	/// btc addresses in their string encoding
+	pub btc: Vec<Vec<u8>>,
+	pub polkadot: Vec<AccountId>,
+	/// Addresses of the chains of the runtime's link verifiers, by chain id
+	pub chains: Vec<(ChainId, Vec<Vec<u8>>)>,
+}
+
+/// An address of another chain which can be linked to an account
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum ExternalAddress<AccountId> {
+	Eth([u8; 20]),
+	/// A btc address in its string encoding
+	Btc(Vec<u8>),
+	Polkadot(AccountId),
+	/// An address of the chain of a link verifier, as it was signed
+	Chain(ChainId, Vec<u8>),
+}
+
+sp_api::decl_runtime_apis! {
+	pub trait AccountLinkerApi<AccountId> where
+		AccountId: Codec,
+	{
+		/// The addresses of every chain linked to `account`
+		fn linked_accounts(account: AccountId) -> LinkedAddresses<AccountId>;
+
+		/// The accounts `address` is linked to
+		fn owner_of(address: ExternalAddress<AccountId>) -> Vec<AccountId>;
+	}
+}
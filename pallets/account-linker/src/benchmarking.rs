@@ -1,35 +1,333 @@
 #![cfg(feature = "runtime-benchmarks")]
 
 use super::*;
+use codec::Encode;
 use frame_benchmarking::{benchmarks, account};
+use frame_support::{storage::bounded_vec::BoundedVec, traits::{Currency, Get}};
 use frame_system::RawOrigin;
-use sp_std::prelude::*;
+use sp_runtime::traits::Bounded;
+use sp_std::{convert::TryFrom, prelude::*};
+use crate::btc::base58::ToBase58;
+use libsecp256k1::{curve::{Affine, Jacobian, Scalar}, PublicKey, SecretKey, ECMULT_GEN_CONTEXT};
 const SEED: u32 = 0;
+const SECRET_KEY: [u8; 32] = [7u8; 32];
+const NONCE: [u8; 32] = [11u8; 32];
+const SOL_KEY_TYPE: sp_runtime::KeyTypeId = sp_runtime::KeyTypeId(*b"sol!");
+
+frame_support::parameter_types! {
+    // the hrp of the benchmarked Cosmos addresses
+    pub const CosmosHrp: &'static [u8] = b"cosmos";
+}
+
+// The link messages commit to the genesis hash of the chain, so they are signed in the benchmark
+// with a fixed key and nonce.
+fn sign(msg: &[u8; 32]) -> ([u8; 32], [u8; 32], u8) {
+    let mut seckey = Scalar::default();
+    let _ = seckey.set_b32(&SECRET_KEY);
+    let mut message = Scalar::default();
+    let _ = message.set_b32(msg);
+    let mut nonce = Scalar::default();
+    let _ = nonce.set_b32(&NONCE);
+
+    let (r, s, recovery_id) = ECMULT_GEN_CONTEXT.sign_raw(&seckey, &message, &nonce).unwrap();
+    (r.b32(), s.b32(), recovery_id)
+}
+
+// The x-only point of `scalar`, which is negated if the point has an odd y as in BIP-340
+fn x_only_point(scalar: &mut Scalar) -> [u8; 32] {
+    let mut pj = Jacobian::default();
+    ECMULT_GEN_CONTEXT.ecmult_gen(&mut pj, scalar);
+    let mut p = Affine::from_gej(&pj);
+    p.x.normalize_var();
+    p.y.normalize_var();
+    if p.y.is_odd() {
+        *scalar = -*scalar;
+    }
+    p.x.b32()
+}
+
+// The BIP-340 signature of `msg` with the fixed key and nonce
+fn sign_schnorr(msg: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let mut seckey = Scalar::default();
+    let _ = seckey.set_b32(&SECRET_KEY);
+    let pk = x_only_point(&mut seckey);
+    let mut nonce = Scalar::default();
+    let _ = nonce.set_b32(&NONCE);
+    let r = x_only_point(&mut nonce);
+
+    let mut e = Scalar::default();
+    let _ = e.set_b32(&btc::schnorr::tagged_hash(b"BIP0340/challenge", &[&r, &pk, msg]));
+    (r, (nonce + e * seckey).b32())
+}
+
+// The x-only public key of the fixed key, the output key of the benchmarked Taproot address
+fn x_only_public_key() -> [u8; 32] {
+    let mut seckey = Scalar::default();
+    let _ = seckey.set_b32(&SECRET_KEY);
+    x_only_point(&mut seckey)
+}
+
+// The account pays the deposit of its new link
+fn fund<T: Config>(account_id: &T::AccountId) {
+    T::Currency::make_free_balance_be(account_id, BalanceOf::<T>::max_value());
+}
+
+// A pending request to link `target` at slot 0, without a deposit
+fn pending_link<T: Config>(target: T::AccountId) -> PolkadotPendingLinkOf<T> {
+    PolkadotPendingLink { target, index: 0, expiring_block_number: T::BlockNumber::from(10000u32), deposit: 0u32.into() }
+}
+
+fn public_key() -> PublicKey {
+    PublicKey::from_secret_key(&SecretKey::parse(&SECRET_KEY).unwrap())
+}
 
 benchmarks!{
     link_eth {
-        let caller = account("caller", 0, 0);
+        let n in 0 .. T::MaxEthLinks::get();
         let account_id: T::AccountId = account("Alice", 0, SEED);
+        let caller = account_id.clone();
+        fund::<T>(&account_id);
+
+        let linked: Vec<[u8; 20]> = (0..n).map(|i| [i as u8; 20]).collect();
+        <EthereumLink<T>>::insert(&account_id, BoundedVec::<_, T::MaxEthLinks>::try_from(linked).unwrap());
 
-        let index: u32 = 0;
-        let addr_expected = [16, 146, 71, 235, 177, 95, 237, 92, 255, 45, 73, 190, 133, 132, 185, 41, 14, 77, 9, 207];
+        let index: u32 = n;
         let expiring_block_number: u32 = 10000;
-        let r = [133, 13, 66, 20, 141, 102, 233, 186, 153, 38, 81, 149, 29, 16, 191, 87, 206, 103, 230, 184, 32, 165, 174, 40, 221, 54, 212, 61, 132, 38, 254, 39];
-        let s = [19, 118, 77, 20, 241, 238, 52, 206, 124, 232, 254, 37, 109, 69, 191, 253, 242, 19, 48, 32, 92, 134, 123, 2, 6, 223, 233, 225, 129, 41, 235, 116];
-        let v: u8 = 28_u8;
-            
+
+        let bytes = Pallet::<T>::link_message(&account_id, expiring_block_number.into());
+        let (r, s, recovery_id) = sign(&util_eth::eth_data_hash(bytes));
+        let v: u64 = (recovery_id + 27).into();
+
+        let pk = public_key().serialize();
+        let mut addr_expected = [0u8; 20];
+        addr_expected.copy_from_slice(&sp_io::hashing::keccak_256(&pk[1..])[12..]);
+
     }:  link_eth(RawOrigin::Signed(caller), account_id.clone(), index, addr_expected, expiring_block_number.into(), r, s, v)
 
+    link_eth_typed {
+        let n in 0 .. T::MaxEthLinks::get();
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        let caller = account_id.clone();
+        fund::<T>(&account_id);
+
+        let linked: Vec<[u8; 20]> = (0..n).map(|i| [i as u8; 20]).collect();
+        <EthereumLink<T>>::insert(&account_id, BoundedVec::<_, T::MaxEthLinks>::try_from(linked).unwrap());
+
+        let index: u32 = n;
+        let expiring_block_number: u32 = 10000;
+        let expiring_block_number: T::BlockNumber = expiring_block_number.into();
+
+        let hash = util_eth::link_typed_data_hash(
+            &Pallet::<T>::eth_typed_data_salt(),
+            &account_id.encode(),
+            &expiring_block_number.encode(),
+            Pallet::<T>::link_nonce(&account_id),
+        ).unwrap();
+        let (r, s, recovery_id) = sign(&hash);
+        let v: u64 = (recovery_id + 27).into();
+
+        let pk = public_key().serialize();
+        let mut addr_expected = [0u8; 20];
+        addr_expected.copy_from_slice(&sp_io::hashing::keccak_256(&pk[1..])[12..]);
+
+    }:  link_eth_typed(RawOrigin::Signed(caller), account_id.clone(), index, addr_expected, expiring_block_number, r, s, v)
+
     link_btc {
-        let caller = account("caller", 0, 0);
+        let n in 0 .. T::MaxBtcLinks::get();
         let account_id: T::AccountId = account("Alice", 0, SEED);
+        let caller = account_id.clone();
+        fund::<T>(&account_id);
+
+        let linked: Vec<Vec<u8>> = (0..n).map(|i| vec![i as u8; 34]).collect();
+        <BitcoinLink<T>>::insert(&account_id, BoundedVec::<_, T::MaxBtcLinks>::try_from(linked).unwrap());
 
-        let index: u32 = 0;
-        let addr_expected = vec![49, 51, 121, 55, 106, 72, 52, 85, 57, 113, 68, 112, 69, 77, 77, 119, 87, 90, 117, 52, 99, 122, 52, 107, 55, 67, 81, 107, 90, 72, 100, 101, 113, 71];
+        let index: u32 = n;
         let expiring_block_number: u32 = 10000;
-        let r = [250, 57, 156, 18, 181, 153, 186, 77, 81, 242, 31, 146, 82, 115, 85, 163, 136, 220, 104, 194, 98, 88, 28, 109, 163, 113, 12, 47, 193, 183, 189, 106];
-        let s = [41, 163, 172, 76, 129, 83, 66, 195, 126, 213, 207, 91, 186, 70, 255, 125, 111, 38, 123, 240, 178, 101, 22, 192, 133, 22, 245, 109, 50, 175, 225, 208];
-        let v: u8 = 0_u8;
-            
+
+        let bytes = Pallet::<T>::link_message(&account_id, expiring_block_number.into());
+        let params = T::BitcoinNetwork::get().params();
+        let (r, s, recovery_id) = sign(&btc::message::signed_msg_hash(params.message_magic, &bytes));
+        // BIP-137 header of a compressed P2PKH address
+        let v: u8 = recovery_id + 31;
+
+        let pk = public_key().serialize_compressed();
+        let addr_expected = btc::legacy::btc_addr_from_pk(&pk, params.p2pkh_version).to_base58();
+
+    }:  link_btc(RawOrigin::Signed(caller), account_id.clone(), index, addr_expected, expiring_block_number.into(), r, s, v)
+
+    // a BIP-322 signature of a Taproot address, whose Schnorr signature is verified in the runtime
+    link_btc_taproot {
+        let n in 0 .. T::MaxBtcLinks::get();
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        let caller = account_id.clone();
+        fund::<T>(&account_id);
+
+        let linked: Vec<Vec<u8>> = (0..n).map(|i| vec![i as u8; 62]).collect();
+        <BitcoinLink<T>>::insert(&account_id, BoundedVec::<_, T::MaxBtcLinks>::try_from(linked).unwrap());
+
+        let index: u32 = n;
+        let expiring_block_number: u32 = 10000;
+
+        let bytes = Pallet::<T>::link_message(&account_id, expiring_block_number.into());
+        let output_key = x_only_public_key();
+        let sighash = btc::bip322::signature_hash(&bytes, &output_key, btc::bip322::SIGHASH_DEFAULT);
+        let (r, s) = sign_schnorr(&sighash);
+        let v: u8 = btc::bip322::SIGHASH_DEFAULT;
+
+        let hrp = T::BitcoinNetwork::get().params().bech32_hrp.unwrap();
+        let addr_expected = btc::witness::WitnessProgram { version: 1, program: output_key.to_vec() }
+            .to_address(hrp.to_vec()).unwrap();
+
     }:  link_btc(RawOrigin::Signed(caller), account_id.clone(), index, addr_expected, expiring_block_number.into(), r, s, v)
+
+    // the origin has `MaxPendingPerOrigin - 1` other pending requests, which are scanned
+    link_polkadot {
+        let caller: T::AccountId = account("caller", 0, 0);
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        fund::<T>(&caller);
+        let expiring_block_number = T::BlockNumber::from(10000u32);
+
+        let pending: Vec<_> = (1..T::MaxPendingPerOrigin::get()).map(|i| pending_link::<T>(account("target", i, SEED))).collect();
+        <PolkadotPending<T>>::insert(&caller, BoundedVec::<_, T::MaxPendingPerOrigin>::try_from(pending).unwrap());
+
+    }:  link_polkadot(RawOrigin::Signed(caller), account_id.clone(), 0, expiring_block_number)
+
+    accept_polkadot {
+        let n in 0 .. T::MaxPolkadotLinks::get();
+        let caller: T::AccountId = account("caller", 0, 0);
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        fund::<T>(&account_id);
+
+        let linked: Vec<T::AccountId> = (0..n).map(|i| account("linked", i, SEED)).collect();
+        <PolkadotLink<T>>::insert(&account_id, BoundedVec::<_, T::MaxPolkadotLinks>::try_from(linked).unwrap());
+        let mut pending = pending_link::<T>(caller.clone());
+        pending.index = n;
+        <PolkadotPending<T>>::insert(&account_id, BoundedVec::<_, T::MaxPendingPerOrigin>::try_from(vec![pending]).unwrap());
+
+    }:  accept_polkadot(RawOrigin::Signed(caller), account_id.clone())
+
+    cancel_polkadot_link {
+        let caller: T::AccountId = account("caller", 0, 0);
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        let expiring_block_number = T::BlockNumber::from(10000u32);
+
+        let pending = pending_link::<T>(account_id.clone());
+        <PolkadotPending<T>>::insert(&caller, BoundedVec::<_, T::MaxPendingPerOrigin>::try_from(vec![pending]).unwrap());
+        <PolkadotPendingExpiry<T>>::insert(expiring_block_number, (caller.clone(), account_id.clone()), ());
+
+    }:  cancel_polkadot_link(RawOrigin::Signed(caller), account_id.clone())
+
+    // the last slot is unlinked by address, which scans all the linked addresses
+    unlink_eth {
+        let n in 1 .. T::MaxEthLinks::get();
+        let caller: T::AccountId = account("caller", 0, 0);
+        let linked: Vec<[u8; 20]> = (0..n).map(|i| [i as u8; 20]).collect();
+        let last = linked[linked.len() - 1];
+        <EthereumLink<T>>::insert(&caller, BoundedVec::<_, T::MaxEthLinks>::try_from(linked).unwrap());
+
+    }:  unlink_eth(RawOrigin::Signed(caller), LinkSlot::Address(last))
+
+    unlink_btc {
+        let n in 1 .. T::MaxBtcLinks::get();
+        let caller: T::AccountId = account("caller", 0, 0);
+        let linked: Vec<Vec<u8>> = (0..n).map(|i| vec![i as u8; 34]).collect();
+        let last = linked[linked.len() - 1].clone();
+        <BitcoinLink<T>>::insert(&caller, BoundedVec::<_, T::MaxBtcLinks>::try_from(linked).unwrap());
+
+    }:  unlink_btc(RawOrigin::Signed(caller), LinkSlot::Address(last))
+
+    unlink_polkadot {
+        let n in 1 .. T::MaxPolkadotLinks::get();
+        let caller: T::AccountId = account("caller", 0, 0);
+        let linked: Vec<T::AccountId> = (0..n).map(|i| account("linked", i, SEED)).collect();
+        let last = linked[linked.len() - 1].clone();
+        <PolkadotLink<T>>::insert(&caller, BoundedVec::<_, T::MaxPolkadotLinks>::try_from(linked).unwrap());
+
+    }:  unlink_polkadot(RawOrigin::Signed(caller), LinkSlot::Address(last))
+
+    // `link` can't be benchmarked without a verifier of the runtime. `WeightInfo::link` is the cost
+    // of the link message and of storing a new link, without any signature recovery, to which the
+    // call adds the weight of the verifier, e.g. `recover_sol`.
+    unlink {
+        let n in 1 .. T::MaxChainLinks::get();
+        let caller: T::AccountId = account("caller", 0, 0);
+        let chain_id: ChainId = SolVerifier::<T>::CHAIN_ID;
+        let linked: Vec<Vec<u8>> = (0..n).map(|i| vec![i as u8; 34]).collect();
+        let last = linked[linked.len() - 1].clone();
+        <ChainLink<T>>::insert(&caller, chain_id, BoundedVec::<_, T::MaxChainLinks>::try_from(linked).unwrap());
+
+    }:  unlink(RawOrigin::Signed(caller), chain_id, LinkSlot::Address(last))
+
+    // the verifiers are benchmarked alone, see `LinkVerifier::weight`
+    recover_eth {
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        let bytes = Pallet::<T>::link_message(&account_id, 10000u32.into());
+        let (r, s, recovery_id) = sign(&util_eth::eth_data_hash(bytes.clone()));
+        let signature = verifiers::join_signature(r, s, recovery_id);
+
+        let pk = public_key().serialize();
+        let addr_expected = sp_io::hashing::keccak_256(&pk[1..])[12..].to_vec();
+
+    }: { EthVerifier::<T>::recover_signer(&addr_expected, &bytes, &signature).unwrap(); }
+
+    // a BIP-137 signature of a compressed P2PKH address
+    recover_bip137 {
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        let bytes = Pallet::<T>::link_message(&account_id, 10000u32.into());
+        let params = T::BitcoinNetwork::get().params();
+        let (r, s, recovery_id) = sign(&btc::message::signed_msg_hash(params.message_magic, &bytes));
+        let signature = verifiers::join_signature(r, s, recovery_id + 31);
+
+        let pk = public_key().serialize_compressed();
+        let addr_expected = btc::legacy::btc_addr_from_pk(&pk, params.p2pkh_version).to_base58();
+
+    }: { BtcVerifier::<T>::recover_signer(&addr_expected, &bytes, &signature).unwrap(); }
+
+    // a BIP-322 signature of a Taproot address, whose Schnorr signature is verified in the runtime
+    recover_bip322 {
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        let bytes = Pallet::<T>::link_message(&account_id, 10000u32.into());
+        let output_key = x_only_public_key();
+        let sighash = btc::bip322::signature_hash(&bytes, &output_key, btc::bip322::SIGHASH_DEFAULT);
+        let (r, s) = sign_schnorr(&sighash);
+        let signature = verifiers::join_signature(r, s, btc::bip322::SIGHASH_DEFAULT);
+
+        let hrp = T::BitcoinNetwork::get().params().bech32_hrp.unwrap();
+        let addr_expected = btc::witness::WitnessProgram { version: 1, program: output_key.to_vec() }
+            .to_address(hrp.to_vec()).unwrap();
+
+    }: { BtcVerifier::<T>::recover_signer(&addr_expected, &bytes, &signature).unwrap(); }
+
+    recover_sol {
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        let bytes = Pallet::<T>::link_message(&account_id, 10000u32.into());
+        let pk = sp_io::crypto::ed25519_generate(SOL_KEY_TYPE, None);
+        let signature = sp_io::crypto::ed25519_sign(SOL_KEY_TYPE, &pk, &bytes).unwrap().0.to_vec();
+        let addr_expected = pk.0.to_base58();
+
+    }: { SolVerifier::<T>::recover_signer(&addr_expected, &bytes, &signature).unwrap(); }
+
+    recover_cosmos {
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        let bytes = Pallet::<T>::link_message(&account_id, 10000u32.into());
+        let pk = public_key().serialize_compressed();
+        let addr_expected = util_cosmos::cosmos_addr_from_pk(CosmosHrp::get(), &pk).unwrap();
+        let (r, s, _) = sign(&util_cosmos::adr036_sign_doc_hash(&addr_expected, &bytes));
+        let mut signature = r.to_vec();
+        signature.extend_from_slice(&s);
+
+    }: { CosmosVerifier::<T, CosmosHrp>::recover_signer(&addr_expected, &bytes, &signature).unwrap(); }
+
+    recover_tron {
+        let account_id: T::AccountId = account("Alice", 0, SEED);
+        let bytes = Pallet::<T>::link_message(&account_id, 10000u32.into());
+        let (r, s, recovery_id) = sign(&util_tron::tron_data_hash(bytes.clone()));
+        let signature = verifiers::join_signature(r, s, recovery_id);
+
+        let pk = public_key().serialize();
+        let mut addr = [0u8; 20];
+        addr.copy_from_slice(&sp_io::hashing::keccak_256(&pk[1..])[12..]);
+        let addr_expected = util_tron::tron_addr(&addr);
+
+    }: { TronVerifier::<T>::recover_signer(&addr_expected, &bytes, &signature).unwrap(); }
 }
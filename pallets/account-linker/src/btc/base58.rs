@@ -54,9 +54,42 @@ impl ToBase58 for [u8] {
 	}
 }
 
+/// A trait for converting a base58 encoded string to a value.
+pub trait FromBase58 {
+	/// Converts a base58 encoded string of `self` to its bytes, `None` if it has an invalid character.
+	fn from_base58(&self) -> Option<Vec<u8>>;
+}
+
+impl FromBase58 for [u8] {
+	fn from_base58(&self) -> Option<Vec<u8>> {
+		let zcount = self.iter().take_while(|x| **x == b'1').count();
+		// big endian bytes of the value after the leading zeros
+		let mut bytes: Vec<u8> = Vec::new();
+
+		for c in self[zcount..].iter() {
+			let mut carry = ALPHABET.iter().position(|a| a == c)? as u32;
+
+			for b in bytes.iter_mut().rev() {
+				carry += 58 * *b as u32;
+				*b = (carry & 0xff) as u8;
+				carry >>= 8;
+			}
+
+			while carry > 0 {
+				bytes.insert(0, (carry & 0xff) as u8);
+				carry >>= 8;
+			}
+		}
+
+		let mut result = vec![0u8; zcount];
+		result.append(&mut bytes);
+		Some(result)
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use super::ToBase58;
+	use super::{FromBase58, ToBase58};
 	use std::str::from_utf8;
 	use hex::decode;
 
@@ -108,6 +141,13 @@ mod tests {
 			let (input, output) = test_case;
 			let input = decode(input).unwrap();
 			assert_eq!(from_utf8(&input.to_base58()).unwrap(), output);
+			assert_eq!(output.as_bytes().from_base58(), Some(input));
 		}
 	}
+
+	#[test]
+	fn test_from_base58_invalid_character() {
+		assert!(b"1NS17iag9jJgTHD1VXjvLCEnZuQ3rJDE9L".from_base58().is_some());
+		assert_eq!(b"0OIl".from_base58(), None);
+	}
 }
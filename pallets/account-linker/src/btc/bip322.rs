@@ -0,0 +1,145 @@
+//! BIP-322 "simple" signed message of a Taproot (P2TR) key path spend
+//! https://github.com/bitcoin/bips/blob/master/bip-0322.mediawiki
+use sp_std::prelude::*;
+use sha2::{Digest, Sha256};
+use super::legacy::dsha256;
+use super::schnorr;
+
+const OP_0: u8 = 0x00;
+const OP_1: u8 = 0x51;
+const OP_PUSHBYTES_32: u8 = 0x20;
+const OP_RETURN: u8 = 0x6a;
+
+pub const SIGHASH_DEFAULT: u8 = 0x00;
+pub const SIGHASH_ALL: u8 = 0x01;
+
+/// Tagged hash of the message committed in the `to_spend` transaction
+pub fn message_hash(msg: &[u8]) -> [u8; 32] {
+	schnorr::tagged_hash(b"BIP0322-signed-message", &[msg])
+}
+
+/// Verify a BIP-322 simple signature, i.e. the single witness item of a P2TR key path spend.
+/// `output_key` is the x-only Taproot output key committed in the address and `sig` is a
+/// 64-byte (SIGHASH_DEFAULT) or 65-byte (SIGHASH_ALL) Schnorr signature.
+pub fn verify_p2tr(msg: &[u8], output_key: &[u8; 32], sig: &[u8]) -> bool {
+	let hash_type = match sig.len() {
+		64 => SIGHASH_DEFAULT,
+		65 if sig[64] == SIGHASH_ALL => SIGHASH_ALL,
+		_ => return false,
+	};
+
+	let mut signature = [0u8; 64];
+	signature.copy_from_slice(&sig[..64]);
+	schnorr::verify(&signature, &signature_hash(msg, output_key, hash_type), output_key)
+}
+
+/// The hash signed by a BIP-322 simple signature of `msg` by the Taproot output key `output_key`
+pub fn signature_hash(msg: &[u8], output_key: &[u8; 32], hash_type: u8) -> [u8; 32] {
+	let mut script_pubkey = vec![OP_1, OP_PUSHBYTES_32];
+	script_pubkey.extend_from_slice(output_key);
+
+	let txid = to_spend_txid(&message_hash(msg), &script_pubkey);
+	to_sign_sighash(&txid, &script_pubkey, hash_type)
+}
+
+/// Txid of the virtual `to_spend` transaction, in internal byte order
+fn to_spend_txid(msg_hash: &[u8; 32], script_pubkey: &[u8]) -> [u8; 32] {
+	let mut tx = Vec::new();
+	// version
+	tx.extend_from_slice(&0u32.to_le_bytes());
+	// single input spending 0000...0000:0xFFFFFFFF, with scriptSig OP_0 PUSH32[message_hash]
+	tx.push(1);
+	tx.extend_from_slice(&[0u8; 32]);
+	tx.extend_from_slice(&0xffff_ffffu32.to_le_bytes());
+	tx.push(34);
+	tx.push(OP_0);
+	tx.push(OP_PUSHBYTES_32);
+	tx.extend_from_slice(msg_hash);
+	tx.extend_from_slice(&0u32.to_le_bytes());
+	// single output of value 0 to the address
+	tx.push(1);
+	tx.extend_from_slice(&0u64.to_le_bytes());
+	tx.push(script_pubkey.len() as u8);
+	tx.extend_from_slice(script_pubkey);
+	// locktime
+	tx.extend_from_slice(&0u32.to_le_bytes());
+	dsha256(&tx)
+}
+
+/// BIP-341 signature hash of the virtual `to_sign` transaction, which spends the output of
+/// `to_spend` by key path to a single OP_RETURN output of value 0
+fn to_sign_sighash(to_spend_txid: &[u8; 32], script_pubkey: &[u8], hash_type: u8) -> [u8; 32] {
+	let sha256 = |data: &[u8]| -> Vec<u8> { Sha256::digest(data).to_vec() };
+
+	let mut prevouts = to_spend_txid.to_vec();
+	prevouts.extend_from_slice(&0u32.to_le_bytes());
+
+	let mut script_pubkeys = vec![script_pubkey.len() as u8];
+	script_pubkeys.extend_from_slice(script_pubkey);
+
+	let mut outputs = 0u64.to_le_bytes().to_vec();
+	outputs.push(1);
+	outputs.push(OP_RETURN);
+
+	let mut sig_msg = Vec::new();
+	// epoch
+	sig_msg.push(0);
+	sig_msg.push(hash_type);
+	// version and locktime
+	sig_msg.extend_from_slice(&0u32.to_le_bytes());
+	sig_msg.extend_from_slice(&0u32.to_le_bytes());
+	sig_msg.append(&mut sha256(&prevouts));
+	sig_msg.append(&mut sha256(&0u64.to_le_bytes()));
+	sig_msg.append(&mut sha256(&script_pubkeys));
+	sig_msg.append(&mut sha256(&0u32.to_le_bytes()));
+	sig_msg.append(&mut sha256(&outputs));
+	// key path spend without annex
+	sig_msg.push(0);
+	// input index
+	sig_msg.extend_from_slice(&0u32.to_le_bytes());
+
+	schnorr::tagged_hash(b"TapSighash", &[&sig_msg])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hex::decode;
+
+	#[test]
+	fn test_message_hash() {
+		assert_eq!(message_hash(b"").to_vec(), decode("c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1").unwrap());
+		assert_eq!(message_hash(b"Hello World").to_vec(), decode("f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a").unwrap());
+	}
+
+	#[test]
+	fn test_to_spend_txid() {
+		// P2WPKH scriptPubKey of bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l
+		let script_pubkey = decode("00142b05d564e6a7a33c087f16e0f730d1440123799d").unwrap();
+
+		let mut txid = to_spend_txid(&message_hash(b""), &script_pubkey);
+		txid.reverse();
+		assert_eq!(txid.to_vec(), decode("c5680aa69bb8d860bf82d4e9cd3504b55dde018de765a91bb566283c545a99a7").unwrap());
+
+		let mut txid = to_spend_txid(&message_hash(b"Hello World"), &script_pubkey);
+		txid.reverse();
+		assert_eq!(txid.to_vec(), decode("b79d196740ad5217771c1098fc4a4b51e0535c32236c71f1ea4d61a2d603352b").unwrap());
+	}
+
+	/// P2TR test vector of BIP-322, address bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3
+	#[test]
+	fn test_verify_p2tr() {
+		let mut output_key = [0u8; 32];
+		output_key.copy_from_slice(&decode("0b34f2cc6f60d54e3fdc2d1dd053fcc393bd2db9acc8de4a7c3cc28a83d4d8e9").unwrap());
+		// witness stack with a single 65-byte item
+		let witness = decode("0141ddebd3eb25012ffa82937d9f25f9644e047bb2f472ab6c5089bbb53588ada2884cb5bcc53911f32d8dcf9548733b694d120db6a4e485194559e8d8fe668d269f01").unwrap();
+		let sig = &witness[2..];
+
+		assert!(verify_p2tr(b"Hello World", &output_key, sig));
+		assert!(!verify_p2tr(b"Hello World!", &output_key, sig));
+		// Only SIGHASH_ALL is accepted for 65-byte signatures
+		let mut sig_none = sig.to_vec();
+		sig_none[64] = 0x02;
+		assert!(!verify_p2tr(b"Hello World", &output_key, &sig_none));
+	}
+}
@@ -1,17 +1,53 @@
 use sha2::{Digest, Sha256};
 use ripemd160::Ripemd160;
+use super::base58::FromBase58;
 
-pub fn btc_addr_from_pk(pk: &[u8]) -> [u8; 25] {
+/// P2PKH address of a public key, `version` is the P2PKH version byte of the network
+pub fn btc_addr_from_pk(pk: &[u8], version: u8) -> [u8; 25] {
+    addr_from_hash(version, &hash160(pk))
+}
+
+/// P2SH-P2WPKH address of a compressed public key.
+/// The redeem script is the P2WPKH witness program: OP_0 followed by a push of the keyhash
+/// (i.e. 0x0014{20-byte keyhash}), and the address commits to the hash160 of the redeem script.
+/// `version` is the P2SH version byte of the network.
+/// https://github.com/bitcoin/bips/blob/master/bip-0049.mediawiki
+pub fn p2sh_p2wpkh_addr_from_pk(pk: &[u8], version: u8) -> [u8; 25] {
+    let mut redeem_script = [0u8; 22];
+    redeem_script[0] = 0;
+    redeem_script[1] = 20;
+    redeem_script[2..].copy_from_slice(&hash160(pk));
+
+    addr_from_hash(version, &hash160(&redeem_script))
+}
+
+/// Base58check payload of a version byte followed by a 20-byte hash
+pub fn addr_from_hash(version: u8, hash: &[u8; 20]) -> [u8; 25] {
     let mut result = [0u8; 25];
 
-    // Now only support P2PKH (Mainnet) prefix = 0
-    result[0] = 0;
-    result[1..21].copy_from_slice(&hash160(pk));
+    result[0] = version;
+    result[1..21].copy_from_slice(hash);
     let cs = checksum(&result[0..21]);
     result[21..25].copy_from_slice(&cs);
     result
 }
 
+/// Version byte of a base58check encoded P2PKH or P2SH address, `None` if the address is malformed
+pub fn addr_version(addr: &[u8]) -> Option<u8> {
+    addr_hash(addr).map(|(version, _)| version)
+}
+
+/// Version byte and 20-byte hash of a base58check encoded address, `None` if the address is malformed
+pub fn addr_hash(addr: &[u8]) -> Option<(u8, [u8; 20])> {
+    let payload = addr.from_base58()?;
+    if payload.len() != 25 || checksum(&payload[0..21]) != payload[21..25] {
+        return None;
+    }
+    let mut hash = [0u8; 20];
+    hash.copy_from_slice(&payload[1..21]);
+    Some((payload[0], hash))
+}
+
 pub fn hash160(bytes: &[u8]) -> [u8; 20] {
     let mut hasher_sha256 = Sha256::new();
     hasher_sha256.update(bytes);
@@ -38,7 +74,7 @@ fn checksum(input: &[u8]) -> [u8; 4] {
 ///
 /// # Returns
 /// * The double SHA256 hash encoded as LE bytes from data
-fn dsha256(bytes: &[u8]) -> [u8; 32] {
+pub fn dsha256(bytes: &[u8]) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(bytes);
     let digest = hasher.finalize();
@@ -55,6 +91,7 @@ fn dsha256(bytes: &[u8]) -> [u8; 32] {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use super::super::base58::ToBase58;
 	use hex::decode;
 
 	#[test]
@@ -77,7 +114,7 @@ mod tests {
         let mut pk_input = [0u8; 65];
         pk_input[0..65].copy_from_slice(&pk[0..65]);
 
-        let addr = btc_addr_from_pk(&pk_input);
+        let addr = btc_addr_from_pk(&pk_input, 0);
 
         let addr_expected_hex = decode("00010966776006953D5567439E5E39F86A0D273BEED61967F6").unwrap();
         let mut addr_expected = [0u8; 25];
@@ -85,4 +122,35 @@ mod tests {
         assert_eq!(addr, addr_expected);
     }
 
-}
\ No newline at end of file
+    // test vector of BIP-49
+    #[test]
+    fn correct_p2sh_p2wpkh_addr_from_pk() {
+        let pk = decode("03a1af804ac108a8a51782198c2d034b28bf90c8803f5a53f76276fa69a4eae77f").unwrap();
+
+        let addr = p2sh_p2wpkh_addr_from_pk(&pk, 5);
+
+        assert_eq!(addr[0], 5);
+        assert_eq!(addr[1..21], decode("336caa13e08b96080a32b5d818d59b4ab3b36742").unwrap()[..]);
+        assert_eq!(std::str::from_utf8(&addr.to_base58()).unwrap(), "36NvZTcMsMowbt78wPzJaHHWaNiyR73Y4g");
+    }
+
+    // testnet test vector of BIP-49
+    #[test]
+    fn correct_testnet_p2sh_p2wpkh_addr_from_pk() {
+        let pk = decode("03a1af804ac108a8a51782198c2d034b28bf90c8803f5a53f76276fa69a4eae77f").unwrap();
+
+        let addr = p2sh_p2wpkh_addr_from_pk(&pk, 0xc4);
+
+        assert_eq!(std::str::from_utf8(&addr.to_base58()).unwrap(), "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2");
+    }
+
+    #[test]
+    fn correct_addr_version() {
+        assert_eq!(addr_version(b"36NvZTcMsMowbt78wPzJaHHWaNiyR73Y4g"), Some(5));
+        assert_eq!(addr_version(b"2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2"), Some(0xc4));
+        // bad checksum
+        assert_eq!(addr_version(b"36NvZTcMsMowbt78wPzJaHHWaNiyR73Y4h"), None);
+        assert_eq!(addr_version(b"bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"), None);
+    }
+
+}
@@ -0,0 +1,101 @@
+//! Bitcoin signed message, compatible with `signmessage` of Bitcoin Core and
+//! the signature header byte of BIP-137 used by Electrum, Trezor, Ledger, etc.
+//! Litecoin and Dogecoin sign messages the same way, with their own message magic.
+//! https://github.com/bitcoin/bips/blob/master/bip-0137.mediawiki
+use sp_std::prelude::*;
+use super::legacy::dsha256;
+
+/// Address type (and key format) declared by the header byte of a signature
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigType {
+	P2PKHUncompressed,
+	P2PKHCompressed,
+	P2SHP2WPKH,
+	P2WPKH,
+}
+
+/// Decode the BIP-137 header byte into the recovery id and the signing address type
+pub fn decode_header(header: u8) -> Result<(u8, SigType), &'static str> {
+	let sig_type = match header {
+		27..=30 => SigType::P2PKHUncompressed,
+		31..=34 => SigType::P2PKHCompressed,
+		35..=38 => SigType::P2SHP2WPKH,
+		39..=42 => SigType::P2WPKH,
+		_ => return Err("InvalidHeader"),
+	};
+	Ok(((header - 27) % 4, sig_type))
+}
+
+/// Double SHA256 of the message prefixed with the message magic of the chain and its length
+pub fn signed_msg_hash(magic: &[u8], msg: &[u8]) -> [u8; 32] {
+	let mut data = magic.to_vec();
+	data.append(&mut varint(msg.len() as u64));
+	data.extend_from_slice(msg);
+	dsha256(&data)
+}
+
+/// Bitcoin variable length integer (CompactSize)
+fn varint(n: u64) -> Vec<u8> {
+	let mut result = Vec::new();
+	if n < 0xfd {
+		result.push(n as u8);
+	} else if n <= 0xffff {
+		result.push(0xfd);
+		result.extend_from_slice(&(n as u16).to_le_bytes());
+	} else if n <= 0xffff_ffff {
+		result.push(0xfe);
+		result.extend_from_slice(&(n as u32).to_le_bytes());
+	} else {
+		result.push(0xff);
+		result.extend_from_slice(&n.to_le_bytes());
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::legacy::btc_addr_from_pk;
+	use super::super::base58::ToBase58;
+	use super::super::network::BTCNetwork;
+	use std::str::from_utf8;
+	use hex::decode;
+
+	#[test]
+	fn test_varint() {
+		assert_eq!(varint(0), vec![0]);
+		assert_eq!(varint(0xfc), vec![0xfc]);
+		assert_eq!(varint(0xfd), vec![0xfd, 0xfd, 0x00]);
+		assert_eq!(varint(0x1234), vec![0xfd, 0x34, 0x12]);
+		assert_eq!(varint(0x10000), vec![0xfe, 0x00, 0x00, 0x01, 0x00]);
+		assert_eq!(varint(0x1_0000_0000), vec![0xff, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00]);
+	}
+
+	#[test]
+	fn test_decode_header() {
+		assert_eq!(decode_header(27), Ok((0, SigType::P2PKHUncompressed)));
+		assert_eq!(decode_header(32), Ok((1, SigType::P2PKHCompressed)));
+		assert_eq!(decode_header(38), Ok((3, SigType::P2SHP2WPKH)));
+		assert_eq!(decode_header(40), Ok((1, SigType::P2WPKH)));
+		assert!(decode_header(26).is_err());
+		assert!(decode_header(43).is_err());
+	}
+
+	/// Test vector of Bitcoin Core `test/functional/rpc_signmessage.py`
+	#[test]
+	fn test_recover_signed_msg() {
+		let sig_bytes = decode("20d6d59d6e1ee8f7919acbf6420bbc36ea29beb56391cc686feb17f0e7191b44802e15b26d48f330b3dd02c5c8e3a61919bd0a4134628bec16210cd1a46fd4f92d").unwrap();
+		let (recovery_id, sig_type) = decode_header(sig_bytes[0]).unwrap();
+		assert_eq!(sig_type, SigType::P2PKHCompressed);
+
+		let mut sig = [0u8; 65];
+		sig[0..64].copy_from_slice(&sig_bytes[1..65]);
+		sig[64] = recovery_id;
+
+		let msg = signed_msg_hash(BTCNetwork::Mainnet.params().message_magic, b"This is just a test message");
+		let pk = sp_io::crypto::secp256k1_ecdsa_recover_compressed(&sig, &msg).unwrap();
+
+		// mainnet P2PKH address of testnet address mpLQjfK79b7CCV4VMJWEWAj5Mpx8Up5zxB
+		assert_eq!(from_utf8(&btc_addr_from_pk(&pk, 0).to_base58()).unwrap(), "19pTScE8LZfwRNasdjXrgFWkVqMRcU99GK");
+	}
+}
@@ -1,3 +1,7 @@
 pub mod base58;
+pub mod bip322;
 pub mod legacy;
-pub mod witness;
\ No newline at end of file
+pub mod message;
+pub mod network;
+pub mod schnorr;
+pub mod witness;
@@ -0,0 +1,114 @@
+use codec::{Encode, Decode};
+use frame_support::RuntimeDebug;
+
+/// Bitcoin network of the linked addresses, which determines the base58 version bytes
+/// and the bech32 human readable part of every address type
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum BTCNetwork {
+	Mainnet,
+	Testnet,
+	Signet,
+	Regtest,
+}
+
+impl BTCNetwork {
+	/// Version byte of P2PKH addresses
+	pub fn p2pkh_version(&self) -> u8 {
+		match self {
+			BTCNetwork::Mainnet => 0x00,
+			_ => 0x6f,
+		}
+	}
+
+	/// Version byte of P2SH addresses
+	pub fn p2sh_version(&self) -> u8 {
+		match self {
+			BTCNetwork::Mainnet => 0x05,
+			_ => 0xc4,
+		}
+	}
+
+	/// Leading characters of base58 encoded P2PKH addresses
+	pub fn p2pkh_prefixes(&self) -> &'static [u8] {
+		match self {
+			BTCNetwork::Mainnet => b"1",
+			_ => b"mn",
+		}
+	}
+
+	/// Leading characters of base58 encoded P2SH addresses
+	pub fn p2sh_prefixes(&self) -> &'static [u8] {
+		match self {
+			BTCNetwork::Mainnet => b"3",
+			_ => b"2",
+		}
+	}
+
+	/// Human readable part of SegWit addresses
+	pub fn bech32_hrp(&self) -> &'static [u8] {
+		match self {
+			BTCNetwork::Mainnet => b"bc",
+			BTCNetwork::Testnet | BTCNetwork::Signet => b"tb",
+			BTCNetwork::Regtest => b"bcrt",
+		}
+	}
+
+	/// Address and message parameters of the network
+	pub fn params(&self) -> UtxoChainParams {
+		UtxoChainParams {
+			p2pkh_version: self.p2pkh_version(),
+			p2pkh_prefixes: self.p2pkh_prefixes(),
+			p2sh_version: Some(self.p2sh_version()),
+			p2sh_prefixes: self.p2sh_prefixes(),
+			bech32_hrp: Some(self.bech32_hrp()),
+			taproot: true,
+			message_magic: BITCOIN_MESSAGE_MAGIC,
+		}
+	}
+}
+
+const BITCOIN_MESSAGE_MAGIC: &[u8] = b"\x18Bitcoin Signed Message:\n";
+
+/// Address and message parameters of a UTXO chain derived from Bitcoin.
+/// P2SH addresses are linked as P2SH-P2WPKH, so chains without SegWit have no `p2sh_version`.
+#[derive(Encode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct UtxoChainParams {
+	/// Version byte of P2PKH addresses
+	pub p2pkh_version: u8,
+	/// Leading characters of base58 encoded P2PKH addresses
+	pub p2pkh_prefixes: &'static [u8],
+	/// Version byte of P2SH-P2WPKH addresses
+	pub p2sh_version: Option<u8>,
+	/// Leading characters of base58 encoded P2SH-P2WPKH addresses
+	pub p2sh_prefixes: &'static [u8],
+	/// Human readable part of SegWit addresses
+	pub bech32_hrp: Option<&'static [u8]>,
+	/// Whether Taproot addresses can be linked with BIP-322 signatures
+	pub taproot: bool,
+	/// Prefix of signed messages, starting with its length
+	pub message_magic: &'static [u8],
+}
+
+impl UtxoChainParams {
+	/// Litecoin mainnet, its P2SH addresses start with `M` and the legacy `3` version is not linked
+	pub const LITECOIN: UtxoChainParams = UtxoChainParams {
+		p2pkh_version: 0x30,
+		p2pkh_prefixes: b"L",
+		p2sh_version: Some(0x32),
+		p2sh_prefixes: b"M",
+		bech32_hrp: Some(b"ltc"),
+		taproot: false,
+		message_magic: b"\x19Litecoin Signed Message:\n",
+	};
+
+	/// Dogecoin mainnet, which has no SegWit so only P2PKH addresses are linked
+	pub const DOGECOIN: UtxoChainParams = UtxoChainParams {
+		p2pkh_version: 0x1e,
+		p2pkh_prefixes: b"D",
+		p2sh_version: None,
+		p2sh_prefixes: b"",
+		bech32_hrp: None,
+		taproot: false,
+		message_magic: b"\x19Dogecoin Signed Message:\n",
+	};
+}
@@ -0,0 +1,110 @@
+//! BIP-340 Schnorr signature verification over secp256k1
+//! https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+use sp_std::prelude::*;
+use sha2::{Digest, Sha256};
+use libsecp256k1::curve::{Affine, Field, Jacobian, Scalar};
+use libsecp256k1::ECMULT_CONTEXT;
+
+/// SHA256(SHA256(tag) || SHA256(tag) || data), see BIP-340
+pub fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> [u8; 32] {
+	let tag_hash = Sha256::digest(tag);
+
+	let mut hasher = Sha256::new();
+	hasher.update(&tag_hash);
+	hasher.update(&tag_hash);
+	for d in data {
+		hasher.update(d);
+	}
+
+	let mut ret = [0u8; 32];
+	ret.copy_from_slice(&hasher.finalize()[..]);
+	ret
+}
+
+/// Verify a 64-byte BIP-340 signature of `msg` against a 32-byte x-only public key
+pub fn verify(sig: &[u8; 64], msg: &[u8], pubkey: &[u8; 32]) -> bool {
+	// Lift x to the point with even y
+	let mut px = Field::default();
+	if !px.set_b32(pubkey) {
+		return false
+	}
+	let mut p = Affine::default();
+	if !p.set_xo_var(&px, false) {
+		return false
+	}
+
+	let mut r_bytes = [0u8; 32];
+	r_bytes.copy_from_slice(&sig[..32]);
+	let mut rx = Field::default();
+	if !rx.set_b32(&r_bytes) {
+		return false
+	}
+
+	let mut s_bytes = [0u8; 32];
+	s_bytes.copy_from_slice(&sig[32..]);
+	let mut s = Scalar::default();
+	if bool::from(s.set_b32(&s_bytes)) {
+		return false
+	}
+
+	// e = int(hash_BIP0340/challenge(r || P || m)) mod n
+	let e_bytes = tagged_hash(b"BIP0340/challenge", &[&r_bytes, pubkey, msg]);
+	let mut e = Scalar::default();
+	let _ = e.set_b32(&e_bytes);
+
+	// R = s⋅G - e⋅P
+	let mut rj = Jacobian::default();
+	ECMULT_CONTEXT.ecmult(&mut rj, &Jacobian::from_ge(&p), &-e, &s);
+	if rj.is_infinity() {
+		return false
+	}
+
+	let mut r = Affine::from_gej(&rj);
+	r.x.normalize_var();
+	r.y.normalize_var();
+	!r.y.is_odd() && r.x.eq_var(&rx)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hex::decode;
+
+	fn verify_hex(pubkey: &str, msg: &str, sig: &str) -> bool {
+		let mut pk = [0u8; 32];
+		pk.copy_from_slice(&decode(pubkey).unwrap());
+		let mut signature = [0u8; 64];
+		signature.copy_from_slice(&decode(sig).unwrap());
+		verify(&signature, &decode(msg).unwrap(), &pk)
+	}
+
+	/// https://github.com/bitcoin/bips/blob/master/bip-0340/test-vectors.csv
+	#[test]
+	fn test_verify_bip340_vectors() {
+		let vectors = vec![
+			("F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9", "0000000000000000000000000000000000000000000000000000000000000000", "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0", true),
+			("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A", true),
+			("DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8", "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C", "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7", true),
+			("25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517", "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF", "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3", true),
+			("D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9", "4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703", "00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C6376AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4", true),
+			// public key not on the curve
+			("EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+			// has_even_y(R) is false
+			("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A14602975563CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2", false),
+			// negated message
+			("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD", false),
+			// negated s value
+			("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6", false),
+			// sig[0:32] is equal to field size
+			("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+			// sig[32:64] is equal to curve order
+			("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141", false),
+			// public key exceeds field size
+			("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+		];
+
+		for (pubkey, msg, sig, expected) in vectors {
+			assert_eq!(verify_hex(pubkey, msg, sig), expected, "{} {}", pubkey, sig);
+		}
+	}
+}
@@ -30,6 +30,7 @@ pub struct WitnessProgram {
 
 impl WitnessProgram {
     /// Converts a Witness Program to a SegWit Address
+    /// Version 0 uses bech32 (BIP-173), version 1+ uses bech32m (BIP-350)
     pub fn to_address(&self, hrp: Vec<u8>) -> Result<Vec<u8>, &'static str> {
         // Verify that the program is valid
         let mut data: Vec<u8> = vec![self.version];
@@ -37,10 +38,40 @@ impl WitnessProgram {
         let p5 = self.program.to_base32();
         // let p5 = convert_bits(self.program.to_vec(), 8, 5, true)?;
         data.extend_from_slice(&p5);
-        let b32 = data.encode(hrp)?;
+        let b32 = data.encode(hrp, Variant::for_version(self.version))?;
         Ok(b32)
     }
 
+    /// Extracts a WitnessProgram out of a SegWit Address with the expected hrp
+    pub fn from_address(hrp: &[u8], address: &[u8]) -> Result<Self, &'static str> {
+        let (addr_hrp, data, variant) = decode(address)?;
+        if addr_hrp != hrp {
+            return Err("InvalidHrp")
+        }
+        if data.len() < 1 {
+            return Err("InvalidLength")
+        }
+        let version = data[0];
+        if version > 16 {
+            return Err("InvalidVersion")
+        }
+        if variant != Variant::for_version(version) {
+            return Err("InvalidVariant")
+        }
+        // Convert 5-bit program into 8-bit
+        let program = data[1..].from_base32()?;
+        if program.len() < 2 || program.len() > 40 {
+            return Err("InvalidProgramLength")
+        }
+        if version == 0 && program.len() != 20 && program.len() != 32 {
+            return Err("InvalidProgramLength")
+        }
+        Ok(WitnessProgram {
+            version,
+            program
+        })
+    }
+
     /// Extracts a WitnessProgram out of a provided script public key
     pub fn from_scriptpubkey(pubkey: &[u8]) -> Result<Self, &'static str> {
         // We need a version byte and a program length byte, with a program at 
@@ -69,18 +100,45 @@ impl WitnessProgram {
 const SEP: u8 = b'1';
 const ALPHABET: &'static [u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
 
+/// Checksum variant, bech32 (BIP-173) or bech32m (BIP-350)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+const BECH32M_CONST: u32 = 0x2bc830a3;
+
+impl Variant {
+    /// Witness version 0 is encoded with bech32, version 1 and above with bech32m
+    pub fn for_version(version: u8) -> Self {
+        if version == 0 {
+            Variant::Bech32
+        } else {
+            Variant::Bech32m
+        }
+    }
+
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => BECH32M_CONST,
+        }
+    }
+}
+
 pub trait Bech32 {
-    fn encode(&self, hrp: Vec<u8>) -> Result<Vec<u8>, &'static str>;
+    fn encode(&self, hrp: Vec<u8>, variant: Variant) -> Result<Vec<u8>, &'static str>;
 }
 
 impl Bech32 for [u8] {
-    fn encode(&self, hrp: Vec<u8>) -> Result<Vec<u8>, &'static str> {
+    fn encode(&self, hrp: Vec<u8>, variant: Variant) -> Result<Vec<u8>, &'static str> {
         if hrp.len() < 1 {
             return Err("invalidData")
         }
 
         let mut combined: Vec<u8> = self.clone().to_vec();
-        combined.extend_from_slice(&create_checksum(&hrp, &self.to_vec()));
+        combined.extend_from_slice(&create_checksum(&hrp, &self.to_vec(), variant));
         let mut encoded = hrp;
         encoded.push(SEP);
         for p in combined {
@@ -107,12 +165,52 @@ fn hrp_expand(hrp: &Vec<u8>) -> Vec<u8> {
     v
 }
 
-fn create_checksum(hrp: &Vec<u8>, data: &Vec<u8>) -> Vec<u8> {
+/// Decodes a bech32 or bech32m string into its hrp, 5-bit data (without checksum) and variant
+pub fn decode(s: &[u8]) -> Result<(Vec<u8>, Vec<u8>, Variant), &'static str> {
+    if s.len() < 8 || s.len() > 90 {
+        return Err("InvalidLength")
+    }
+    // Mixed case is not allowed
+    let has_lower = s.iter().any(|c| c.is_ascii_lowercase());
+    let has_upper = s.iter().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err("MixedCase")
+    }
+    let s: Vec<u8> = s.iter().map(|c| c.to_ascii_lowercase()).collect();
+
+    let pos = s.iter().rposition(|c| *c == SEP).ok_or("NoSeparator")?;
+    if pos < 1 || pos + 7 > s.len() {
+        return Err("InvalidSeparatorPosition")
+    }
+    let hrp = s[..pos].to_vec();
+    if hrp.iter().any(|c| *c < 33 || *c > 126) {
+        return Err("InvalidHrp")
+    }
+
+    let mut data: Vec<u8> = Vec::new();
+    for c in &s[pos + 1..] {
+        let v = ALPHABET.iter().position(|a| a == c).ok_or("InvalidChar")?;
+        data.push(v as u8);
+    }
+
+    let mut values: Vec<u8> = hrp_expand(&hrp);
+    values.extend_from_slice(&data);
+    let variant = match polymod(values) {
+        1 => Variant::Bech32,
+        BECH32M_CONST => Variant::Bech32m,
+        _ => return Err("InvalidChecksum"),
+    };
+
+    data.truncate(data.len() - 6);
+    Ok((hrp, data, variant))
+}
+
+fn create_checksum(hrp: &Vec<u8>, data: &Vec<u8>, variant: Variant) -> Vec<u8> {
     let mut values: Vec<u8> = hrp_expand(hrp);
     values.extend_from_slice(data);
     // Pad with 6 zeros
     values.extend_from_slice(&[0u8; 6]);
-    let plm: u32 = polymod(values) ^ 1;
+    let plm: u32 = polymod(values) ^ variant.constant();
     let mut checksum: Vec<u8> = Vec::new();
     for p in 0..6 {
         checksum.push(((plm >> 5 * (5 - p)) & 0x1f) as u8);
@@ -215,6 +313,39 @@ impl ToBase32 for [u8] {
 	}
 }
 
+/// A trait for converting a base32 (5-bit) value back to bytes.
+pub trait FromBase32 {
+    /// Converts a 5-bit value of `self` to 8-bit, rejecting non zero or more than 4 bits of padding.
+    fn from_base32(&self) -> Result<Vec<u8>, &'static str>;
+}
+
+impl FromBase32 for [u8] {
+    fn from_base32(&self) -> Result<Vec<u8>, &'static str> {
+        let mut acc: u32 = 0;
+        let mut bits: u32 = 0;
+        let mut result = Vec::new();
+
+        for value in self.iter() {
+            if *value >= 32 {
+                return Err("InvalidData")
+            }
+            acc = (acc << 5) | *value as u32;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                result.push((acc >> bits) as u8);
+            }
+            acc &= (1 << bits) - 1;
+        }
+
+        if bits >= 5 || acc != 0 {
+            return Err("InvalidPadding")
+        }
+
+        Ok(result)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -222,7 +353,7 @@ mod tests {
 
 	#[test]
 	fn test_to_base32_basic() {
-		assert_eq!(from_utf8(&vec![0x00, 0x01, 0x02].encode(b"bech32".to_vec()).unwrap()).unwrap(), "bech321qpz4nc4pe");
+		assert_eq!(from_utf8(&vec![0x00, 0x01, 0x02].encode(b"bech32".to_vec(), Variant::Bech32).unwrap()).unwrap(), "bech321qpz4nc4pe");
     }
 
     #[test]
@@ -236,7 +367,7 @@ mod tests {
                 ]
             ),
             (
-                "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7k7grplx",
+                "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y",
                 vec![
                     0x51, 0x28, 0x75, 0x1e, 0x76, 0xe8, 0x19, 0x91, 0x96, 0xd4, 0x54,
                     0x94, 0x1c, 0x45, 0xd1, 0xb3, 0xa3, 0x23, 0xf1, 0x43, 0x3b, 0xd6,
@@ -245,13 +376,13 @@ mod tests {
                 ]
             ),
             (
-                "BC1SW50QA3JX3S",
+                "BC1SW50QGDZ25J",
                 vec![
                    0x60, 0x02, 0x75, 0x1e
                 ]
             ),
             (
-                "bc1zw508d6qejxtdg4y5r3zarvaryvg6kdaj",
+                "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs",
                 vec![
                     0x52, 0x10, 0x75, 0x1e, 0x76, 0xe8, 0x19, 0x91, 0x96, 0xd4, 0x54,
                     0x94, 0x1c, 0x45, 0xd1, 0xb3, 0xa3, 0x23
@@ -272,6 +403,65 @@ mod tests {
 
             let enc_address = enc_result.unwrap();
             assert_eq!(address.to_lowercase(), from_utf8(&enc_address).unwrap().to_lowercase());
+
+            let dec_result = WitnessProgram::from_address(b"bc", address.as_bytes());
+            assert!(dec_result.is_ok());
+            let dec_prog = dec_result.unwrap();
+            assert_eq!(dec_prog.version, prog.version);
+            assert_eq!(dec_prog.program, prog.program);
+        }
+    }
+
+    /// https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki#test-vectors
+    #[test]
+    fn valid_bech32m_address() {
+        let pairs: Vec<(&str, &str)> = vec![
+            ("tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7", "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262"),
+            ("tb1qqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesrxh6hy", "0020000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433"),
+            ("tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c", "5120000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433"),
+            ("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0", "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+        ];
+        for (address, scriptpubkey) in pairs {
+            let hrp = address[..2].as_bytes();
+            let prog = WitnessProgram::from_address(hrp, address.as_bytes()).unwrap();
+            let expected = WitnessProgram::from_scriptpubkey(&hex::decode(scriptpubkey).unwrap()).unwrap();
+            assert_eq!(prog.version, expected.version);
+            assert_eq!(prog.program, expected.program);
+            assert_eq!(from_utf8(&prog.to_address(hrp.to_vec()).unwrap()).unwrap(), address);
+        }
+    }
+
+    /// https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki#test-vectors
+    #[test]
+    fn invalid_bech32m_address() {
+        let addresses = vec![
+            // Invalid human-readable part
+            "tc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq5zuyut",
+            // Invalid checksums (Bech32 instead of Bech32m and vice versa)
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+            "tb1z0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqglt7rf",
+            "BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL",
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh",
+            "tb1q0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq24jc47",
+            // Invalid character in checksum
+            "bc1p38j9r5y49hruaue7wxjce0updqjuyyx0kh56v8s25huc6995vvpql3jow4",
+            // Invalid witness version
+            "BC130XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ7ZWS8R",
+            // Invalid program length
+            "bc1pw5dgrnzv",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v8n0nx0muaewav253zgeav",
+            "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P",
+            // Mixed case
+            "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq47Zagq",
+            // More than 4 padding bits, non zero padding
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v07qwwzcrf",
+            "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vpggkg4j",
+            // Empty data section
+            "bc1gmk9yu",
+        ];
+        for address in addresses {
+            assert!(WitnessProgram::from_address(b"bc", address.as_bytes()).is_err(), "{}", address);
+            assert!(WitnessProgram::from_address(b"tb", address.as_bytes()).is_err(), "{}", address);
         }
     }
     
@@ -1,6 +1,10 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
+pub use btc::network::{BTCNetwork, UtxoChainParams};
+pub use account_linker_runtime_api::{ChainId, ExternalAddress, LinkedAddresses};
+pub use traits::{LinkDelegates, LinkedAccountsProvider, LinkVerifier, LinkVerifiers, OnLinkChanged};
+pub use verifiers::{BtcVerifier, CosmosVerifier, DogeVerifier, EthVerifier, LtcVerifier, SolVerifier, TronVerifier};
 
 #[cfg(test)]
 mod mock;
@@ -9,45 +13,215 @@ mod mock;
 mod tests;
 
 mod btc;
+mod links;
 mod util_eth;
+mod util_cosmos;
+mod util_tron;
 mod benchmarking;
+pub mod migrations;
+pub mod traits;
+pub mod verifiers;
 pub mod weights;
 
+use frame_support::traits::Currency;
+use sp_std::prelude::*;
+
+type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type PolkadotPendingLinkOf<T> = PolkadotPendingLink<
+	<T as frame_system::Config>::AccountId,
+	<T as frame_system::Config>::BlockNumber,
+	BalanceOf<T>,
+>;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use crate::*;
 	use frame_system::pallet_prelude::*;
-	use codec::Encode;
-	use sp_std::prelude::*;
-	use sp_io::crypto::secp256k1_ecdsa_recover_compressed;
-	use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*,};
-	use frame_system::{ensure_signed};
+	use codec::{Encode, Decode};
+	use sp_std::{convert::TryFrom, mem, prelude::*};
+	use sp_io::crypto::{secp256k1_ecdsa_recover, secp256k1_ecdsa_recover_compressed};
+	use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*, storage::bounded_vec::BoundedVec, traits::{PalletInfoAccess, ReservableCurrency}};
+	use frame_system::{ensure_none, ensure_signed};
+	use sp_runtime::{DispatchError, SaturatedConversion, traits::{IdentifyAccount, Saturating, Verify, Zero}};
 	use btc::base58::ToBase58;
 	use btc::witness::WitnessProgram;
+	use btc::message::SigType;
 	use weights::WeightInfo;
+	use crate::links::{LinkStore, EthLinks, BtcLinks, PolkadotLinks, ChainLinks};
 	pub const EXPIRING_BLOCK_NUMBER_MAX: u32 = 10 * 60 * 24 * 30; // 30 days for 6s per block
-	pub const MAX_ETH_LINKS: usize = 3;
-	pub const MAX_BTC_LINKS: usize = 3;
-	pub const MAX_POLKADOT_LINKS: usize = 3;
+	/// The blocks of pending polkadot link expiries read in a block, besides the `MaxPendingExpiriesPerBlock`
+	/// requests. More than one block is read so that the cursor catches up after falling behind.
+	pub const EXPIRY_BLOCKS_SCANNED_MAX: u32 = 8;
 
 	enum BTCAddrType {
 		Legacy,
+		NestedSegwit,
 		Segwit,
+		Taproot,
+	}
+
+	/// Selects a linked address, either by its slot index or by the address itself.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub enum LinkSlot<Address> {
+		Index(u32),
+		Address(Address),
+	}
+
+	/// The chain of the addresses of a link store, keying the deposits of its slots
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum LinkChain {
+		Eth,
+		Btc,
+		Polkadot,
+		/// The chain of a verifier of `Config::LinkVerifiers`
+		Chain(ChainId),
+	}
+
+	/// Which signed origins may link an address to an account
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum LinkPolicy {
+		/// Only the account itself
+		SelfOnly,
+		/// The account itself or one of its delegates, see `Config::LinkDelegates`
+		SelfOrProxy,
+		/// Any signed origin or an unsigned transaction, the link signature being the only authorisation
+		Anyone,
+	}
+
+	/// A pending request of an origin to link the polkadot account `target` at slot `index`
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct PolkadotPendingLink<AccountId, BlockNumber, Balance> {
+		/// The polkadot account to link
+		pub target: AccountId,
+		/// The slot index of the link
+		pub index: u32,
+		/// The block number the request expires at
+		pub expiring_block_number: BlockNumber,
+		/// The deposit reserved from the origin for the request
+		pub deposit: Balance,
+	}
+
+	/// Storage version of the pallet.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum Releases {
+		/// Linked addresses are stored in unbounded vectors.
+		V1_0_0,
+		/// Linked addresses are stored in vectors bounded by the link limits of each chain.
+		V2_0_0,
+		/// Linked addresses are indexed by the accounts they are linked to.
+		V3_0_0,
+		/// Pending polkadot link requests are stored per origin, with a target, an expiring block number and a deposit.
+		V4_0_0,
+	}
+
+	impl Default for Releases {
+		fn default() -> Self {
+			Releases::V1_0_0
+		}
 	}
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 		type WeightInfo: WeightInfo;
+		/// The Bitcoin network of linked btc addresses, determines the accepted address prefixes
+		#[pallet::constant]
+		type BitcoinNetwork: Get<BTCNetwork>;
+		/// The maximum number of eth addresses linked to an account
+		#[pallet::constant]
+		type MaxEthLinks: Get<u32>;
+		/// The maximum number of btc addresses linked to an account
+		#[pallet::constant]
+		type MaxBtcLinks: Get<u32>;
+		/// The maximum number of polkadot accounts linked to an account
+		#[pallet::constant]
+		type MaxPolkadotLinks: Get<u32>;
+		/// Whether an address can only be linked to a single account
+		#[pallet::constant]
+		type UniqueLinks: Get<bool>;
+		/// The signature of a polkadot account over its link message, e.g. `MultiSignature`
+		type Signature: Parameter + Verify<Signer = Self::Signer>;
+		/// The signer of `Signature`, identifying the polkadot account
+		type Signer: IdentifyAccount<AccountId = Self::AccountId>;
+		/// The currency in which the link deposits are reserved
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// The deposit reserved from an account for each linked eth address
+		#[pallet::constant]
+		type EthLinkDeposit: Get<BalanceOf<Self>>;
+		/// The deposit reserved from an account for each linked btc address
+		#[pallet::constant]
+		type BtcLinkDeposit: Get<BalanceOf<Self>>;
+		/// The deposit reserved from an account for each linked polkadot account
+		#[pallet::constant]
+		type PolkadotLinkDeposit: Get<BalanceOf<Self>>;
+		/// Handler notified when the links of an account change
+		type OnLinkChanged: OnLinkChanged<Self::AccountId>;
+		/// The priority of unsigned link transactions in the transaction pool
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+		/// The minimum number of blocks between two unsigned links of an account
+		#[pallet::constant]
+		type UnsignedLinkInterval: Get<Self::BlockNumber>;
+		/// Which signed origins may link addresses to an account.
+		/// Unsigned links are authorised by their link signature alone under `LinkPolicy::Anyone`,
+		/// and under the other policies only with the signature of the account, see `unsigned_link_message`.
+		#[pallet::constant]
+		type LinkPolicy: Get<LinkPolicy>;
+		/// The delegates which may link addresses on behalf of an account under `LinkPolicy::SelfOrProxy`
+		type LinkDelegates: LinkDelegates<Self::AccountId>;
+		/// The verifiers of the chains linked with `link`, a tuple of `LinkVerifier`s with unique chain ids,
+		/// e.g. `(SolVerifier<Runtime>, CosmosVerifier<Runtime, CosmosHrp>)`. Eth and btc are always linked
+		/// with `EthVerifier` and `BtcVerifier`, so their chain ids must not be reused.
+		type LinkVerifiers: LinkVerifiers;
+		/// The maximum number of addresses of each chain of `LinkVerifiers` linked to an account
+		#[pallet::constant]
+		type MaxChainLinks: Get<u32>;
+		/// The deposit reserved from an account for each linked address of a chain of `LinkVerifiers`
+		#[pallet::constant]
+		type ChainLinkDeposit: Get<BalanceOf<Self>>;
+		/// The maximum number of pending polkadot link requests of an origin
+		#[pallet::constant]
+		type MaxPendingPerOrigin: Get<u32>;
+		/// The maximum number of expired polkadot link requests pruned in a block.
+		/// The rest are pruned in the next blocks.
+		#[pallet::constant]
+		type MaxPendingExpiriesPerBlock: Get<u32>;
+		/// The deposit reserved from an origin for each of its pending polkadot link requests
+		#[pallet::constant]
+		type PolkadotRequestDeposit: Get<BalanceOf<Self>>;
+		/// The maximum number of unsigned links in a block.
+		/// Unsigned links don't reserve a deposit, so this and `UnsignedLinkInterval` limit their spam.
+		#[pallet::constant]
+		type MaxUnsignedLinksPerBlock: Get<u32>;
 	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	#[pallet::metadata(T::AccountId = "AccountId")]
+	#[pallet::metadata(T::AccountId = "AccountId", T::BlockNumber = "BlockNumber")]
 	pub enum Event<T: Config> {
-		EthAddressLinked(T::AccountId, Vec<u8>),
-		BtcAddressLinked(T::AccountId, Vec<u8>),
-		PolkadotAddressLinked(T::AccountId, T::AccountId),
+		/// An eth address was linked at a slot, replacing the address of the slot if any.
+		/// \[account, address, index, replaced\]
+		EthAddressLinked(T::AccountId, Vec<u8>, u32, Option<Vec<u8>>),
+		/// A btc address was linked at a slot, replacing the address of the slot if any.
+		/// \[account, address, index, replaced\]
+		BtcAddressLinked(T::AccountId, Vec<u8>, u32, Option<Vec<u8>>),
+		/// A polkadot account was linked at a slot, replacing the account of the slot if any.
+		/// \[account, linked, index, replaced\]
+		PolkadotAddressLinked(T::AccountId, T::AccountId, u32, Option<T::AccountId>),
+		/// An address of the chain of a link verifier was linked at a slot, replacing the address of
+		/// the slot if any. \[account, chain_id, address, index, replaced\]
+		AddressLinked(T::AccountId, ChainId, Vec<u8>, u32, Option<Vec<u8>>),
+		EthAddressUnlinked(T::AccountId, Vec<u8>),
+		BtcAddressUnlinked(T::AccountId, Vec<u8>),
+		PolkadotAddressUnlinked(T::AccountId, T::AccountId),
+		/// An address of the chain of a link verifier was unlinked. \[account, chain_id, address\]
+		AddressUnlinked(T::AccountId, ChainId, Vec<u8>),
+		/// A request to link a polkadot account is pending until the block number. \[origin, target, expiring_block_number\]
+		PolkadotLinkRequested(T::AccountId, T::AccountId, T::BlockNumber),
+		/// A pending request to link a polkadot account was cancelled. \[origin, target\]
+		PolkadotLinkCancelled(T::AccountId, T::AccountId),
+		/// A pending request to link a polkadot account expired. \[origin, target\]
+		PolkadotLinkExpired(T::AccountId, T::AccountId),
 	}
 
 	#[pallet::error]
@@ -55,41 +229,297 @@ pub mod pallet {
 		EcdsaRecoverFailure,
 		LinkRequestExpired,
 		UnexpectedAddress,
-		// Unexpected ethereum message length error
+		// No longer returned since eth messages of any length are hashed; kept so that the indices
+		// of the following errors don't change
 		UnexpectedEthMsgLength,
 		InvalidBTCAddress,
 		InvalidBTCAddressLength,
 		InvalidExpiringBlockNumber,
 		WrongPendingRequest,
+		// BIP-137 signature header is unknown or doesn't match the address type
+		InvalidBTCSignatureHeader,
+		SchnorrVerifyFailure,
+		// The requested slot index or address is not linked to the account
+		LinkNotFound,
+		// The number of linked addresses exceeds the limit of the chain
+		TooManyLinks,
+		// The address is already linked to another account
+		AddressAlreadyLinked,
+		// The signature of the polkadot account doesn't match the link message
+		InvalidPolkadotSignature,
+		// The `s` of the ethereum signature is in the upper half of the curve order
+		MalleableEthSignature,
+		// The account made an unsigned link less than `UnsignedLinkInterval` blocks ago
+		UnsignedLinkTooSoon,
+		// The origin may not link addresses to the account under the link policy
+		UnauthorizedOrigin,
+		// The Solana address isn't the base58 encoding of a 32-byte public key
+		InvalidSolAddress,
+		// The ed25519 signature of the Solana address doesn't match the link message
+		InvalidSolSignature,
+		// The Cosmos address isn't a bech32 address with the hrp of the chain
+		InvalidCosmosAddress,
+		// The ADR-036 signature doesn't match the Cosmos address and the link message
+		InvalidCosmosSignature,
+		// The Tron address isn't a base58check address with the Tron version byte
+		InvalidTronAddress,
+		// No verifier of `LinkVerifiers` has the chain id
+		UnknownChain,
+		// The link message can't be encoded as EIP-712 typed data, e.g. a block number wider than uint256
+		InvalidTypedData,
+		// The origin has `MaxPendingPerOrigin` pending polkadot link requests
+		TooManyPendingLinks,
+		// The block already has `MaxUnsignedLinksPerBlock` unsigned links
+		TooManyUnsignedLinks,
+		// The address is already linked to the account in another slot
+		DuplicateLink,
+		// The account of an unsigned link doesn't exist, i.e. it has no providers
+		UnknownAccount,
+		// The eth address isn't 20 bytes
+		InvalidEthAddress,
+		// The signature doesn't have the length of the signatures of the chain
+		InvalidSignatureLength,
 	}
 
 	#[pallet::hooks]
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+			if StorageVersion::<T>::get() == Releases::V1_0_0 {
+				weight = weight.saturating_add(migrations::migrate_to_v2::<T>());
+			}
+			if StorageVersion::<T>::get() == Releases::V2_0_0 {
+				weight = weight.saturating_add(migrations::migrate_to_v3::<T>());
+			}
+			if StorageVersion::<T>::get() == Releases::V3_0_0 {
+				weight = weight.saturating_add(migrations::migrate_to_v4::<T>());
+			}
+			weight
+		}
+
+		/// Prune the pending polkadot link requests expired at block `n` or before, returning their deposits.
+		/// At most `MaxPendingExpiriesPerBlock` requests of `EXPIRY_BLOCKS_SCANNED_MAX` blocks are read,
+		/// the rest are pruned in the next blocks.
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			let mut budget = T::MaxPendingExpiriesPerBlock::get() as usize;
+			let mut block = <PolkadotExpiryCursor<T>>::get().unwrap_or(n);
+			let mut scanned: Weight = 0;
+			let mut expired: Weight = 0;
+			while block <= n && budget > 0 && scanned < EXPIRY_BLOCKS_SCANNED_MAX as Weight {
+				// one more request is read to know if the block is exhausted
+				let mut requests: Vec<(T::AccountId, T::AccountId)> = <PolkadotPendingExpiry<T>>::iter_prefix(block)
+					.map(|(request, _)| request)
+					.take(budget + 1)
+					.collect();
+				scanned += 1;
+				let exhausted = requests.len() <= budget;
+				requests.truncate(budget);
+				budget -= requests.len();
+				for (origin, target) in requests {
+					expired += 1;
+					Self::remove_polkadot_pending(&origin, &target);
+					Self::deposit_event(Event::PolkadotLinkExpired(origin, target));
+				}
+				if !exhausted {
+					break;
+				}
+				block = block.saturating_add(1u32.into());
+			}
+			<PolkadotExpiryCursor<T>>::put(block);
+			T::DbWeight::get().reads_writes(1 + scanned + expired * 3, 1 + expired * 3)
+		}
+
+		fn integrity_test() {
+			assert!(T::MaxEthLinks::get() > 0, "MaxEthLinks must be greater than 0");
+			assert!(T::MaxBtcLinks::get() > 0, "MaxBtcLinks must be greater than 0");
+			assert!(T::MaxPolkadotLinks::get() > 0, "MaxPolkadotLinks must be greater than 0");
+			assert!(T::MaxChainLinks::get() > 0, "MaxChainLinks must be greater than 0");
+			assert!(T::MaxPendingPerOrigin::get() > 0, "MaxPendingPerOrigin must be greater than 0");
+			assert!(T::MaxPendingExpiriesPerBlock::get() > 0, "MaxPendingExpiriesPerBlock must be greater than 0");
+
+			let mut chain_ids = T::LinkVerifiers::chain_ids();
+			let verifiers = chain_ids.len();
+			chain_ids.sort_unstable();
+			chain_ids.dedup();
+			assert!(chain_ids.len() == verifiers, "Chain ids of LinkVerifiers must be unique");
+			assert!(!chain_ids.contains(&EthVerifier::<T>::CHAIN_ID) && !chain_ids.contains(&BtcVerifier::<T>::CHAIN_ID),
+				"Chain ids of LinkVerifiers must not be the chain ids of eth and btc");
+		}
+	}
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::storage]
-	#[pallet::getter(fn eth_addresses)]
-	pub(super) type EthereumLink<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, Vec<[u8; 20]>, ValueQuery>;
+	pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
+	#[pallet::storage]
+	pub(super) type EthereumLink<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<[u8; 20], T::MaxEthLinks>, ValueQuery>;
 
 	#[pallet::storage]
-	#[pallet::getter(fn btc_addresses)]
-	pub(super) type BitcoinLink<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, Vec<Vec<u8>>, ValueQuery>;
+	pub(super) type BitcoinLink<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<Vec<u8>, T::MaxBtcLinks>, ValueQuery>;
 
 	#[pallet::storage]
-	#[pallet::getter(fn polkadot_addresses)]
-	pub(super) type PolkadotLink<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, Vec<T::AccountId>, ValueQuery>;
+	pub(super) type PolkadotLink<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<T::AccountId, T::MaxPolkadotLinks>, ValueQuery>;
 
+	/// Addresses of each chain of `T::LinkVerifiers` linked to an account, as they were signed
 	#[pallet::storage]
-	#[pallet::getter(fn polkadot_pending)]
-	pub(super) type PolkadotPending<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, (T::AccountId, u32), ValueQuery>;
+	pub(super) type ChainLink<T: Config> =  StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Twox64Concat, ChainId, BoundedVec<Vec<u8>, T::MaxChainLinks>, ValueQuery>;
+
+	/// Accounts an eth address is linked to
+	#[pallet::storage]
+	pub(super) type EthereumLinkOwner<T: Config> =  StorageDoubleMap<_, Blake2_128Concat, [u8; 20], Blake2_128Concat, T::AccountId, ()>;
+
+	/// Accounts a btc address is linked to
+	#[pallet::storage]
+	pub(super) type BitcoinLinkOwner<T: Config> =  StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, T::AccountId, ()>;
+
+	/// Accounts a polkadot account is linked to
+	#[pallet::storage]
+	pub(super) type PolkadotLinkOwner<T: Config> =  StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, ()>;
+
+	/// Accounts an address of a chain of `T::LinkVerifiers` is linked to
+	#[pallet::storage]
+	pub(super) type ChainLinkOwner<T: Config> =  StorageDoubleMap<_, Blake2_128Concat, (ChainId, Vec<u8>), Blake2_128Concat, T::AccountId, ()>;
+
+	/// Nonce of the link messages signed for an account, bumped on each successful link
+	#[pallet::storage]
+	#[pallet::getter(fn link_nonce)]
+	pub(super) type LinkNonce<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Deposit reserved from an account for its links
+	#[pallet::storage]
+	#[pallet::getter(fn link_deposit)]
+	pub(super) type LinkDeposit<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+	/// Deposits reserved from an account for the slots of its links to a chain, in slot order.
+	/// Slots linked before deposits were recorded hold none and have no entry.
+	#[pallet::storage]
+	#[pallet::getter(fn slot_deposits)]
+	pub(super) type SlotDeposits<T: Config> =  StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Twox64Concat, LinkChain, Vec<BalanceOf<T>>, ValueQuery>;
+
+	/// Pending requests of an origin to link polkadot accounts
+	#[pallet::storage]
+	#[pallet::getter(fn polkadot_pending_links)]
+	pub(super) type PolkadotPending<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<PolkadotPendingLinkOf<T>, T::MaxPendingPerOrigin>, ValueQuery>;
+
+	/// Pending polkadot link requests `(origin, target)` by their expiring block number
+	#[pallet::storage]
+	pub(super) type PolkadotPendingExpiry<T: Config> =  StorageDoubleMap<_, Twox64Concat, T::BlockNumber, Blake2_128Concat, (T::AccountId, T::AccountId), ()>;
+
+	/// The earliest block number of the pending polkadot link requests which may be expired but not pruned yet
+	#[pallet::storage]
+	pub(super) type PolkadotExpiryCursor<T: Config> =  StorageValue<_, T::BlockNumber>;
+
+	/// Block number of the last unsigned link of an account
+	#[pallet::storage]
+	#[pallet::getter(fn last_unsigned_link)]
+	pub(super) type LastUnsignedLink<T: Config> =  StorageMap<_, Blake2_128Concat, T::AccountId, T::BlockNumber>;
+
+	/// Number of unsigned links in a block, with its block number
+	#[pallet::storage]
+	pub(super) type UnsignedLinkCount<T: Config> =  StorageValue<_, (T::BlockNumber, u32)>;
+
+	/// Links of the genesis block, e.g. migrated from another chain.
+	/// No deposit is reserved for them and the link limits and uniqueness of the runtime are enforced.
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// The eth addresses linked to each account
+		pub eth_links: Vec<(T::AccountId, Vec<[u8; 20]>)>,
+		/// The btc addresses linked to each account, in their string encoding
+		pub btc_links: Vec<(T::AccountId, Vec<Vec<u8>>)>,
+		/// The polkadot accounts linked to each account
+		pub polkadot_links: Vec<(T::AccountId, Vec<T::AccountId>)>,
+		/// The addresses of the chains of `T::LinkVerifiers` linked to each account
+		pub chain_links: Vec<(T::AccountId, ChainId, Vec<Vec<u8>>)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self {
+				eth_links: Default::default(),
+				btc_links: Default::default(),
+				polkadot_links: Default::default(),
+				chain_links: Default::default(),
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (account, addrs) in self.eth_links.iter() {
+				assert!(!<EthereumLink<T>>::contains_key(account), "Eth links of an account are configured twice");
+				let linked = BoundedVec::<_, T::MaxEthLinks>::try_from(addrs.clone())
+					.expect("Too many eth links of an account");
+				for addr in addrs.iter() {
+					assert!(addrs.iter().filter(|other| *other == addr).count() == 1,
+						"Eth address is linked twice to an account");
+					assert!(Pallet::<T>::ensure_linkable(&EthLinks, addr, account).is_ok(),
+						"Eth address is linked to more than one account");
+					<EthereumLinkOwner<T>>::insert(addr, account, ());
+				}
+				<EthereumLink<T>>::insert(account, linked);
+			}
+
+			let params = T::BitcoinNetwork::get().params();
+			for (account, addrs) in self.btc_links.iter() {
+				assert!(!<BitcoinLink<T>>::contains_key(account), "Btc links of an account are configured twice");
+				let linked = BoundedVec::<_, T::MaxBtcLinks>::try_from(addrs.clone())
+					.expect("Too many btc links of an account");
+				for addr in addrs.iter() {
+					assert!(addrs.iter().filter(|other| *other == addr).count() == 1,
+						"Btc address is linked twice to an account");
+					assert!(Pallet::<T>::is_valid_utxo_addr(&params, addr), "Invalid btc address");
+					assert!(Pallet::<T>::ensure_linkable(&BtcLinks, addr, account).is_ok(),
+						"Btc address is linked to more than one account");
+					<BitcoinLinkOwner<T>>::insert(addr, account, ());
+				}
+				<BitcoinLink<T>>::insert(account, linked);
+			}
+
+			for (account, addrs) in self.polkadot_links.iter() {
+				assert!(!<PolkadotLink<T>>::contains_key(account), "Polkadot links of an account are configured twice");
+				let linked = BoundedVec::<_, T::MaxPolkadotLinks>::try_from(addrs.clone())
+					.expect("Too many polkadot links of an account");
+				for addr in addrs.iter() {
+					assert!(addrs.iter().filter(|other| *other == addr).count() == 1,
+						"Polkadot account is linked twice to an account");
+					assert!(Pallet::<T>::ensure_linkable(&PolkadotLinks, addr, account).is_ok(),
+						"Polkadot account is linked to more than one account");
+					<PolkadotLinkOwner<T>>::insert(addr, account, ());
+				}
+				<PolkadotLink<T>>::insert(account, linked);
+			}
+
+			for (account, chain_id, addrs) in self.chain_links.iter() {
+				assert!(!<ChainLink<T>>::contains_key(account, chain_id), "Chain links of an account are configured twice");
+				let linked = BoundedVec::<_, T::MaxChainLinks>::try_from(addrs.clone())
+					.expect("Too many chain links of an account");
+				for addr in addrs.iter() {
+					assert!(addrs.iter().filter(|other| *other == addr).count() == 1,
+						"Chain address is linked twice to an account");
+					let valid = T::LinkVerifiers::validate_address(*chain_id, addr).expect("Unknown chain id");
+					assert!(valid.is_ok(), "Invalid chain address");
+					assert!(Pallet::<T>::ensure_linkable(&ChainLinks(*chain_id), addr, account).is_ok(),
+						"Chain address is linked to more than one account");
+					<ChainLinkOwner<T>>::insert((chain_id, addr), account, ());
+				}
+				<ChainLink<T>>::insert(account, chain_id, linked);
+			}
+
+			StorageVersion::<T>::put(Releases::V4_0_0);
+		}
+	}
 
 	#[pallet::call]
 	impl<T:Config> Pallet<T> {
 
-		#[pallet::weight(T::WeightInfo::link_eth())]
+		/// Link an eth address with an eth_sign signature of `link_message`, like `link` with the chain
+		/// id of `EthVerifier`. `v` may be the raw recovery id (0/1), 27/28 or an EIP-155 value of any chain id.
+		#[pallet::weight(T::WeightInfo::link_eth(T::MaxEthLinks::get()))]
 		pub fn link_eth(
 			origin: OriginFor<T>,
 			account: T::AccountId,
@@ -98,58 +528,282 @@ pub mod pallet {
 			expiring_block_number: T::BlockNumber,
 			r: [u8; 32],
 			s: [u8; 32],
+			v: u64,
+		) -> DispatchResultWithPostInfo {
+
+			let recovery_id = util_eth::recovery_id(v).ok_or(Error::<T>::EcdsaRecoverFailure)?;
+
+			Self::link(origin, EthVerifier::<T>::CHAIN_ID, account, index, addr_expected.to_vec(), expiring_block_number,
+				verifiers::join_signature(r, s, recovery_id))
+
+		}
+
+		/// Link an eth address with an EIP-712 signature of `LinkLitentry { account, expiringBlock, nonce }`,
+		/// where `account` is the SCALE encoded account. The domain is `{ name: "Litentry", version: "1", salt }`
+		/// and the salt is returned by `eth_typed_data_salt`.
+		#[pallet::weight(T::WeightInfo::link_eth_typed(T::MaxEthLinks::get()))]
+		pub fn link_eth_typed(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+			index: u32,
+			addr_expected: [u8; 20],
+			expiring_block_number: T::BlockNumber,
+			r: [u8; 32],
+			s: [u8; 32],
+			v: u64,
+		) -> DispatchResultWithPostInfo {
+
+			let origin = ensure_signed(origin)?;
+			Self::ensure_link_authorized(&origin, &account)?;
+
+			Self::ensure_not_expired(expiring_block_number)?;
+
+			let hash = util_eth::link_typed_data_hash(
+				&Self::eth_typed_data_salt(),
+				&account.encode(),
+				&expiring_block_number.encode(),
+				Self::link_nonce(&account),
+			).map_err(|_| Error::<T>::InvalidTypedData)?;
+
+			let addr = Self::eth_addr_from_sig(hash, r, s, v)?;
+			ensure!(addr == addr_expected, Error::<T>::UnexpectedAddress);
+
+			Self::insert_link(EthLinks, account, index, addr)
+
+		}
+
+		/// separate sig to r, s, v because runtime only support array parameter with length <= 32
+		/// `v` is the BIP-137 header byte of the signature, which encodes the recovery id and
+		/// the address type. The message is signed as a standard Bitcoin signed message.
+		/// For Taproot (`bc1p`) addresses, `r` and `s` are the BIP-340 signature of a BIP-322
+		/// simple proof and `v` is its sighash type. Their Schnorr signature is verified in the runtime
+		/// rather than by a host function, so they are weighed separately.
+		/// Addresses must belong to the network configured by `T::BitcoinNetwork`.
+		/// The link is made like `link` with the chain id of `BtcVerifier`.
+		#[pallet::weight(Pallet::<T>::link_btc_weight(addr_expected))]
+		pub fn link_btc(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+			index: u32,
+			addr_expected: Vec<u8>,
+			expiring_block_number: T::BlockNumber,
+			r: [u8; 32],
+			s: [u8; 32],
 			v: u8,
 		) -> DispatchResultWithPostInfo {
 
-			let _ = ensure_signed(origin)?;
+			Self::link(origin, BtcVerifier::<T>::CHAIN_ID, account, index, addr_expected, expiring_block_number,
+				verifiers::join_signature(r, s, v))
+
+		}
+
+		/// Request to link the polkadot account `account` to the origin at slot `index`.
+		/// The request is pending until `account` accepts it or it expires at `expiring_block_number`,
+		/// a previous request of the origin for the same account is replaced and keeps its deposit.
+		/// An origin has at most `MaxPendingPerOrigin` pending requests, each holding `PolkadotRequestDeposit`
+		/// until it is accepted, cancelled or pruned.
+		#[pallet::weight(T::WeightInfo::link_polkadot())]
+		pub fn link_polkadot(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+			index: u32,
+			expiring_block_number: T::BlockNumber,
+		) -> DispatchResultWithPostInfo {
+
+			let origin = ensure_signed(origin)?;
 
 			let current_block_number = <frame_system::Pallet<T>>::block_number();
 			ensure!(expiring_block_number > current_block_number, Error::<T>::LinkRequestExpired);
 			ensure!((expiring_block_number - current_block_number) < T::BlockNumber::from(EXPIRING_BLOCK_NUMBER_MAX),
 				Error::<T>::InvalidExpiringBlockNumber);
 
-			let mut bytes = b"Link Litentry: ".encode();
-			let mut account_vec = account.encode();
-			let mut expiring_block_number_vec = expiring_block_number.encode();
+			let mut pending = Self::polkadot_pending_links(&origin);
+			let deposit = match pending.iter().position(|request| request.target == account) {
+				Some(position) => {
+					let previous = pending.remove(position);
+					<PolkadotPendingExpiry<T>>::remove(previous.expiring_block_number, (origin.clone(), account.clone()));
+					previous.deposit
+				},
+				None => {
+					ensure!(pending.len() < T::MaxPendingPerOrigin::get() as usize, Error::<T>::TooManyPendingLinks);
+					let deposit = T::PolkadotRequestDeposit::get();
+					T::Currency::reserve(&origin, deposit)?;
+					deposit
+				},
+			};
+			pending.try_push(PolkadotPendingLink { target: account.clone(), index, expiring_block_number, deposit })
+				.map_err(|_| Error::<T>::TooManyPendingLinks)?;
 
-			bytes.append(&mut account_vec);
-			bytes.append(&mut expiring_block_number_vec);
+			<PolkadotPending<T>>::insert(&origin, pending);
+			<PolkadotPendingExpiry<T>>::insert(expiring_block_number, (origin.clone(), account.clone()), ());
+			Self::deposit_event(Event::PolkadotLinkRequested(origin, account, expiring_block_number));
 
-			let hash = util_eth::eth_data_hash(bytes).map_err(|_| Error::<T>::UnexpectedEthMsgLength)?;
+			Ok(().into())
 
-			let mut msg = [0u8; 32];
-			let mut sig = [0u8; 65];
+		}
 
-			msg[..32].copy_from_slice(&hash[..32]);
-			sig[..32].copy_from_slice(&r[..32]);
-			sig[32..64].copy_from_slice(&s[..32]);
-			sig[64] = v;
+		/// Cancel the pending request of the origin to link the polkadot account `account`.
+		#[pallet::weight(T::WeightInfo::cancel_polkadot_link())]
+		pub fn cancel_polkadot_link(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+		) -> DispatchResultWithPostInfo {
 
-			let addr = util_eth::addr_from_sig(msg, sig)
-				.map_err(|_| Error::<T>::EcdsaRecoverFailure)?;
-			ensure!(addr == addr_expected, Error::<T>::UnexpectedAddress);
+			let origin = ensure_signed(origin)?;
 
-			let index = index as usize;
-			let mut addrs = Self::eth_addresses(&account);
-			// NOTE: allow linking `MAX_ETH_LINKS` eth addresses.
-			if (index >= addrs.len()) && (addrs.len() != MAX_ETH_LINKS) {
-				addrs.push(addr.clone());
-			} else if (index >= addrs.len()) && (addrs.len() == MAX_ETH_LINKS) {
-				addrs[MAX_ETH_LINKS - 1] = addr.clone();
-			} else {
-				addrs[index] = addr.clone();
-			}
+			ensure!(Self::remove_polkadot_pending(&origin, &account).is_some(), Error::<T>::WrongPendingRequest);
+			Self::deposit_event(Event::PolkadotLinkCancelled(origin, account));
+
+			Ok(().into())
+		}
+
+		#[pallet::weight(T::WeightInfo::accept_polkadot(T::MaxPolkadotLinks::get()))]
+		pub fn accept_polkadot(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+
+			let origin = ensure_signed(origin)?;
 
-			<EthereumLink<T>>::insert(account.clone(), addrs);
-			Self::deposit_event(Event::EthAddressLinked(account, addr.to_vec()));
+			let (index, expiring_block_number) = Self::polkadot_pending(&account, &origin)
+				.ok_or(Error::<T>::WrongPendingRequest)?;
+			ensure!(expiring_block_number > <frame_system::Pallet<T>>::block_number(), Error::<T>::LinkRequestExpired);
+
+			Self::insert_link(PolkadotLinks, account.clone(), index, origin.clone())?;
+			Self::remove_polkadot_pending(&account, &origin);
 
 			Ok(().into())
+		}
+
+		/// Link the polkadot account `target` to `account` in a single transaction, with the signature
+		/// of `target` over the link message instead of a pending request accepted by `target`.
+		/// The raw link message and the message wrapped in `<Bytes>..</Bytes>` by polkadot.js signers
+		/// are both accepted.
+		#[pallet::weight(T::WeightInfo::link_polkadot_signed(T::MaxPolkadotLinks::get()))]
+		pub fn link_polkadot_signed(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+			index: u32,
+			target: T::AccountId,
+			expiring_block_number: T::BlockNumber,
+			signature: T::Signature,
+		) -> DispatchResultWithPostInfo {
+
+			let origin = ensure_signed(origin)?;
+			Self::ensure_link_authorized(&origin, &account)?;
+
+			let current_block_number = <frame_system::Pallet<T>>::block_number();
+			ensure!(expiring_block_number > current_block_number, Error::<T>::LinkRequestExpired);
+			ensure!((expiring_block_number - current_block_number) < T::BlockNumber::from(EXPIRING_BLOCK_NUMBER_MAX),
+				Error::<T>::InvalidExpiringBlockNumber);
 
+			let bytes = Self::link_message(&account, expiring_block_number);
+			ensure!(Self::polkadot_signature_valid(&signature, &bytes, &target), Error::<T>::InvalidPolkadotSignature);
+
+			Self::insert_link(PolkadotLinks, account, index, target)
 		}
 
-		/// separate sig to r, s, v because runtime only support array parameter with length <= 32
-		#[pallet::weight(T::WeightInfo::link_btc())]
-		pub fn link_btc(
+		/// Remove one of the origin's linked eth addresses.
+		/// Addresses in later slots are shifted down by one, so slot indexes stay contiguous.
+		#[pallet::weight(T::WeightInfo::unlink_eth(T::MaxEthLinks::get()))]
+		pub fn unlink_eth(
+			origin: OriginFor<T>,
+			slot: LinkSlot<[u8; 20]>,
+		) -> DispatchResultWithPostInfo {
+
+			let account = ensure_signed(origin)?;
+
+			Self::unlink_address(EthLinks, account, slot)
+		}
+
+		/// Remove one of the origin's linked btc addresses.
+		/// Addresses in later slots are shifted down by one, so slot indexes stay contiguous.
+		#[pallet::weight(T::WeightInfo::unlink_btc(T::MaxBtcLinks::get()))]
+		pub fn unlink_btc(
+			origin: OriginFor<T>,
+			slot: LinkSlot<Vec<u8>>,
+		) -> DispatchResultWithPostInfo {
+
+			let account = ensure_signed(origin)?;
+
+			Self::unlink_address(BtcLinks, account, slot)
+		}
+
+		/// Remove one of the origin's linked polkadot accounts.
+		/// Accounts in later slots are shifted down by one, so slot indexes stay contiguous.
+		#[pallet::weight(T::WeightInfo::unlink_polkadot(T::MaxPolkadotLinks::get()))]
+		pub fn unlink_polkadot(
+			origin: OriginFor<T>,
+			slot: LinkSlot<T::AccountId>,
+		) -> DispatchResultWithPostInfo {
+
+			let account = ensure_signed(origin)?;
+
+			Self::unlink_address(PolkadotLinks, account, slot)
+		}
+
+		/// Link an eth address with an EIP-2098 compact signature of the same message as `link_eth`.
+		/// `vs` is the `yParityAndS` half of the signature, the recovery id being its highest bit.
+		#[pallet::weight(T::WeightInfo::link_eth(T::MaxEthLinks::get()))]
+		pub fn link_eth_compact(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+			index: u32,
+			addr_expected: [u8; 20],
+			expiring_block_number: T::BlockNumber,
+			r: [u8; 32],
+			vs: [u8; 32],
+		) -> DispatchResultWithPostInfo {
+
+			let mut signature = r.to_vec();
+			signature.extend_from_slice(&vs);
+
+			Self::link(origin, EthVerifier::<T>::CHAIN_ID, account, index, addr_expected.to_vec(), expiring_block_number, signature)
+
+		}
+
+		/// Link an eth address like `link_eth`, without a signed origin so that accounts without free
+		/// funds can link. The transaction is authenticated by the link signature and no deposit is reserved:
+		/// instead the account must exist, so that links can't be made for fresh accounts for free, an
+		/// account can make an unsigned link once every `UnsignedLinkInterval` blocks and a block has at
+		/// most `MaxUnsignedLinksPerBlock` of them.
+		/// Unless `LinkPolicy` is `Anyone`, `account_signature` must be the signature of `account` over
+		/// `unsigned_link_message`.
+		#[pallet::weight(T::WeightInfo::link_eth(T::MaxEthLinks::get()).saturating_add(T::DbWeight::get().reads_writes(3, 2)))]
+		pub fn link_eth_unsigned(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+			index: u32,
+			addr_expected: [u8; 20],
+			expiring_block_number: T::BlockNumber,
+			r: [u8; 32],
+			s: [u8; 32],
+			v: u64,
+			account_signature: Option<T::Signature>,
+		) -> DispatchResultWithPostInfo {
+
+			ensure_none(origin)?;
+
+			Self::ensure_unsigned_link_allowed(&account)?;
+			Self::ensure_unsigned_link_authorized(&account, index, &addr_expected[..], expiring_block_number, &account_signature)?;
+			let addr = Self::verify_eth_link(&account, addr_expected, expiring_block_number, r, s, v)?;
+
+			Self::insert_link_with_deposit(EthLinks, account.clone(), index, addr, Zero::zero())?;
+			Self::note_unsigned_link(&account);
+
+			Ok(().into())
+
+		}
+
+		/// Link a btc address like `link_btc`, without a signed origin so that accounts without free
+		/// funds can link. The transaction is authenticated by the link signature and no deposit is reserved:
+		/// instead the account must exist, so that links can't be made for fresh accounts for free, an
+		/// account can make an unsigned link once every `UnsignedLinkInterval` blocks and a block has at
+		/// most `MaxUnsignedLinksPerBlock` of them.
+		/// Unless `LinkPolicy` is `Anyone`, `account_signature` must be the signature of `account` over
+		/// `unsigned_link_message`.
+		#[pallet::weight(Pallet::<T>::link_btc_weight(addr_expected).saturating_add(T::DbWeight::get().reads_writes(3, 2)))]
+		pub fn link_btc_unsigned(
 			origin: OriginFor<T>,
 			account: T::AccountId,
 			index: u32,
@@ -158,37 +812,516 @@ pub mod pallet {
 			r: [u8; 32],
 			s: [u8; 32],
 			v: u8,
+			account_signature: Option<T::Signature>,
 		) -> DispatchResultWithPostInfo {
 
-			let _ = ensure_signed(origin)?;
+			ensure_none(origin)?;
 
+			Self::ensure_unsigned_link_allowed(&account)?;
+			Self::ensure_unsigned_link_authorized(&account, index, &addr_expected[..], expiring_block_number, &account_signature)?;
+			let addr = Self::verify_btc_link(&account, &addr_expected, expiring_block_number, r, s, v)?;
+
+			Self::insert_link_with_deposit(BtcLinks, account.clone(), index, addr, Zero::zero())?;
+			Self::note_unsigned_link(&account);
+
+			Ok(().into())
+
+		}
+
+		/// Link an address of the chain `chain_id` with its signature of `link_message`, verified by
+		/// `EthVerifier`, `BtcVerifier` or the verifier of the chain in `T::LinkVerifiers`. The address
+		/// and signature are encoded as defined by the verifier. The signature is recovered once, by the
+		/// verifier, so the weight of the chains of `T::LinkVerifiers` is the storage of the link,
+		/// `WeightInfo::link`, plus the weight of the verifier alone.
+		#[pallet::weight(Pallet::<T>::link_weight(*chain_id, addr_expected))]
+		pub fn link(
+			origin: OriginFor<T>,
+			chain_id: ChainId,
+			account: T::AccountId,
+			index: u32,
+			addr_expected: Vec<u8>,
+			expiring_block_number: T::BlockNumber,
+			signature: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+
+			let origin = ensure_signed(origin)?;
+			Self::ensure_link_authorized(&origin, &account)?;
+
+			let addr = Self::verify_link(&account, &addr_expected, expiring_block_number,
+				|bytes| Self::recover_link_signer(chain_id, &addr_expected, bytes, &signature))?;
+
+			Self::insert_chain_link(chain_id, account, index, addr)
+
+		}
+
+		/// Remove one of the origin's linked addresses of the chain `chain_id`, like `unlink_eth` and
+		/// `unlink_btc` for the chain ids of `EthVerifier` and `BtcVerifier`.
+		/// Addresses in later slots are shifted down by one, so slot indexes stay contiguous.
+		#[pallet::weight(Pallet::<T>::unlink_weight(*chain_id))]
+		pub fn unlink(
+			origin: OriginFor<T>,
+			chain_id: ChainId,
+			slot: LinkSlot<Vec<u8>>,
+		) -> DispatchResultWithPostInfo {
+
+			let account = ensure_signed(origin)?;
+
+			if chain_id == EthVerifier::<T>::CHAIN_ID {
+				let slot = match slot {
+					LinkSlot::Index(index) => LinkSlot::Index(index),
+					LinkSlot::Address(addr) => LinkSlot::Address(<[u8; 20]>::try_from(&addr[..])
+						.map_err(|_| Error::<T>::LinkNotFound)?),
+				};
+				Self::unlink_address(EthLinks, account, slot)
+			} else if chain_id == BtcVerifier::<T>::CHAIN_ID {
+				Self::unlink_address(BtcLinks, account, slot)
+			} else {
+				Self::unlink_address(ChainLinks(chain_id), account, slot)
+			}
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		/// Unsigned links are checked as they would be dispatched: their authorisation under the link policy,
+		/// the link signature, the expiry, the uniqueness of the address, the rate limit of the account
+		/// and the limit of the block.
+		/// They provide the account and its link nonce, so only one of them is in the pool for an
+		/// account, and they are valid until the link message expires.
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let (account, expiring_block_number) = match call {
+				Call::link_eth_unsigned(account, index, addr_expected, expiring_block_number, r, s, v, account_signature) => {
+					Self::ensure_unsigned_link_authorized(account, *index, &addr_expected[..], *expiring_block_number, account_signature)
+						.map_err(Self::invalid_unsigned_link)?;
+					let addr = Self::verify_eth_link(account, *addr_expected, *expiring_block_number, *r, *s, *v)
+						.map_err(Self::invalid_unsigned_link)?;
+					Self::ensure_linkable(&EthLinks, &addr, account).map_err(Self::invalid_unsigned_link)?;
+					(account, *expiring_block_number)
+				},
+				Call::link_btc_unsigned(account, index, addr_expected, expiring_block_number, r, s, v, account_signature) => {
+					Self::ensure_unsigned_link_authorized(account, *index, &addr_expected[..], *expiring_block_number, account_signature)
+						.map_err(Self::invalid_unsigned_link)?;
+					let addr = Self::verify_btc_link(account, addr_expected, *expiring_block_number, *r, *s, *v)
+						.map_err(Self::invalid_unsigned_link)?;
+					Self::ensure_linkable(&BtcLinks, &addr, account).map_err(Self::invalid_unsigned_link)?;
+					(account, *expiring_block_number)
+				},
+				_ => return InvalidTransaction::Call.into(),
+			};
+			Self::ensure_unsigned_link_allowed(account).map_err(Self::invalid_unsigned_link)?;
+
+			let current_block_number = <frame_system::Pallet<T>>::block_number();
+			ValidTransaction::with_tag_prefix("AccountLinker")
+				.priority(T::UnsignedPriority::get())
+				.and_provides((account, Self::link_nonce(account)))
+				.longevity((expiring_block_number - current_block_number).saturated_into::<u64>())
+				.propagate(true)
+				.build()
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The message signed by an address linked to `account`.
+		/// Besides the expiring block number, it commits to the genesis hash and the index of this pallet
+		/// so it can't be replayed on another chain, and to the link nonce of `account` so it can't be
+		/// replayed once the link has been made.
+		pub fn link_message(account: &T::AccountId, expiring_block_number: T::BlockNumber) -> Vec<u8> {
+			let mut bytes = b"Link Litentry: ".encode();
+			let mut account_vec = account.encode();
+			let mut expiring_block_number_vec = expiring_block_number.encode();
+			let mut genesis_hash_vec = <frame_system::Pallet<T>>::block_hash(T::BlockNumber::from(0u32)).encode();
+			let mut nonce_vec = Self::link_nonce(account).encode();
+
+			bytes.append(&mut account_vec);
+			bytes.append(&mut expiring_block_number_vec);
+			bytes.append(&mut genesis_hash_vec);
+			bytes.push(<Self as PalletInfoAccess>::index() as u8);
+			bytes.append(&mut nonce_vec);
+			bytes
+		}
+
+		/// The message signed by `account` to authorise an unsigned link of `addr_expected` at slot `index`
+		/// when `LinkPolicy` isn't `Anyone`: the link message followed by the slot index and the address,
+		/// as passed to the call, so that it can't authorise another link.
+		pub fn unsigned_link_message(
+			account: &T::AccountId,
+			index: u32,
+			addr_expected: &[u8],
+			expiring_block_number: T::BlockNumber,
+		) -> Vec<u8> {
+			let mut bytes = Self::link_message(account, expiring_block_number);
+			bytes.extend_from_slice(&index.encode());
+			bytes.extend_from_slice(addr_expected);
+			bytes
+		}
+
+		/// The eth addresses linked to `account`
+		pub fn eth_addresses(account: &T::AccountId) -> Vec<[u8; 20]> {
+			<EthereumLink<T>>::get(account).into_inner()
+		}
+
+		/// The btc addresses linked to `account`
+		pub fn btc_addresses(account: &T::AccountId) -> Vec<Vec<u8>> {
+			<BitcoinLink<T>>::get(account).into_inner()
+		}
+
+		/// The polkadot accounts linked to `account`
+		pub fn polkadot_addresses(account: &T::AccountId) -> Vec<T::AccountId> {
+			<PolkadotLink<T>>::get(account).into_inner()
+		}
+
+		/// The addresses of the chain `chain_id` linked to `account`
+		pub fn chain_addresses(chain_id: ChainId, account: &T::AccountId) -> Vec<Vec<u8>> {
+			<ChainLink<T>>::get(account, chain_id).into_inner()
+		}
+
+		/// The accounts `addr` is linked to
+		pub fn eth_owners(addr: &[u8; 20]) -> Vec<T::AccountId> {
+			<EthereumLinkOwner<T>>::iter_prefix(addr).map(|(account, _)| account).collect()
+		}
+
+		/// The accounts `addr` is linked to
+		pub fn btc_owners(addr: &[u8]) -> Vec<T::AccountId> {
+			<BitcoinLinkOwner<T>>::iter_prefix(addr).map(|(account, _)| account).collect()
+		}
+
+		/// The accounts `linked` is linked to as a polkadot address
+		pub fn polkadot_owners(linked: &T::AccountId) -> Vec<T::AccountId> {
+			<PolkadotLinkOwner<T>>::iter_prefix(linked).map(|(account, _)| account).collect()
+		}
+
+		/// The accounts the address `addr` of the chain `chain_id` is linked to
+		pub fn chain_owners(chain_id: ChainId, addr: &[u8]) -> Vec<T::AccountId> {
+			<ChainLinkOwner<T>>::iter_prefix((chain_id, addr)).map(|(account, _)| account).collect()
+		}
+
+		/// The addresses of every chain linked to `account`
+		pub fn linked_accounts(account: &T::AccountId) -> LinkedAddresses<T::AccountId> {
+			LinkedAddresses {
+				eth: Self::eth_addresses(account),
+				btc: Self::btc_addresses(account),
+				polkadot: Self::polkadot_addresses(account),
+				chains: Self::chains_addresses(account),
+			}
+		}
+
+		/// The accounts `address` is linked to
+		pub fn owner_of(address: &ExternalAddress<T::AccountId>) -> Vec<T::AccountId> {
+			match address {
+				ExternalAddress::Eth(addr) => Self::eth_owners(addr),
+				ExternalAddress::Btc(addr) => Self::btc_owners(addr),
+				ExternalAddress::Polkadot(linked) => Self::polkadot_owners(linked),
+				ExternalAddress::Chain(chain_id, addr) => Self::chain_owners(*chain_id, addr),
+			}
+		}
+
+		/// The addresses of each chain of `T::LinkVerifiers` linked to `account`, ordered by chain id
+		fn chains_addresses(account: &T::AccountId) -> Vec<(ChainId, Vec<Vec<u8>>)> {
+			let mut chains: Vec<_> = <ChainLink<T>>::iter_prefix(account)
+				.map(|(chain_id, addrs)| (chain_id, addrs.into_inner()))
+				.collect();
+			chains.sort_unstable_by_key(|(chain_id, _)| *chain_id);
+			chains
+		}
+
+		/// An address can be linked to `account` if it isn't linked to other accounts or links aren't unique.
+		/// Unique links have at most one owner, so this reads at most the two first owners of `addr`.
+		fn ensure_linkable<S: LinkStore<T>>(store: &S, addr: &S::Address, account: &T::AccountId) -> Result<(), Error<T>> {
+			if !T::UniqueLinks::get() {
+				return Ok(());
+			}
+			ensure!(!store.linked_to_other(addr, account), Error::<T>::AddressAlreadyLinked);
+			Ok(())
+		}
+
+		/// Whether `addr` is a well-formed address of a linkable type on the chain of `params`:
+		/// P2PKH, P2SH, P2WPKH or P2TR
+		fn is_valid_utxo_addr(params: &UtxoChainParams, addr: &[u8]) -> bool {
+			match params.bech32_hrp {
+				Some(hrp) if addr.starts_with(hrp) && addr[hrp.len()..].starts_with(b"1") => {
+					match WitnessProgram::from_address(hrp, addr) {
+						// only the canonical lowercase encoding is linked
+						Ok(wp) => ((wp.version == 0 && wp.program.len() == 20) || (params.taproot && wp.version == 1 && wp.program.len() == 32))
+							&& wp.to_address(hrp.to_vec()).map_or(false, |canonical| canonical == addr),
+						Err(_) => false,
+					}
+				},
+				_ => match btc::legacy::addr_version(addr) {
+					Some(version) => version == params.p2pkh_version || Some(version) == params.p2sh_version,
+					None => false,
+				},
+			}
+		}
+
+		/// Ensure `expiring_block_number` is in the future, within `EXPIRING_BLOCK_NUMBER_MAX` blocks
+		fn ensure_not_expired(expiring_block_number: T::BlockNumber) -> Result<(), Error<T>> {
 			let current_block_number = <frame_system::Pallet<T>>::block_number();
 			ensure!(expiring_block_number > current_block_number, Error::<T>::LinkRequestExpired);
 			ensure!((expiring_block_number - current_block_number) < T::BlockNumber::from(EXPIRING_BLOCK_NUMBER_MAX),
 				Error::<T>::InvalidExpiringBlockNumber);
+			Ok(())
+		}
+
+		/// Verify the signature of the link message of `account` by `addr_expected`, whose signer is
+		/// derived by `recover` from the link message
+		fn verify_link<E: From<Error<T>>>(
+			account: &T::AccountId,
+			addr_expected: &[u8],
+			expiring_block_number: T::BlockNumber,
+			recover: impl FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+		) -> Result<Vec<u8>, E> {
+			Self::ensure_not_expired(expiring_block_number)?;
+
+			let bytes = Self::link_message(account, expiring_block_number);
+
+			let addr = recover(&bytes)?;
+			ensure!(addr == addr_expected, Error::<T>::UnexpectedAddress);
+
+			Ok(addr)
+		}
+
+		/// Derive the address of the chain `chain_id` which signed `link_message` with `EthVerifier`,
+		/// `BtcVerifier` or the verifier of the chain in `T::LinkVerifiers`
+		fn recover_link_signer(
+			chain_id: ChainId,
+			addr_expected: &[u8],
+			link_message: &[u8],
+			signature: &[u8],
+		) -> Result<Vec<u8>, DispatchError> {
+			<(EthVerifier<T>, BtcVerifier<T>) as LinkVerifiers>::recover_signer(chain_id, addr_expected, link_message, signature)
+				.or_else(|| T::LinkVerifiers::recover_signer(chain_id, addr_expected, link_message, signature))
+				.ok_or(Error::<T>::UnknownChain)?
+		}
+
+		/// Link the verified address `addr` of the chain `chain_id` to `account` at slot `index`,
+		/// in the links of eth and btc for the chain ids of `EthVerifier` and `BtcVerifier`
+		fn insert_chain_link(chain_id: ChainId, account: T::AccountId, index: u32, addr: Vec<u8>) -> DispatchResultWithPostInfo {
+			if chain_id == EthVerifier::<T>::CHAIN_ID {
+				let addr = <[u8; 20]>::try_from(&addr[..]).map_err(|_| Error::<T>::InvalidEthAddress)?;
+				Self::insert_link(EthLinks, account, index, addr)
+			} else if chain_id == BtcVerifier::<T>::CHAIN_ID {
+				Self::insert_link(BtcLinks, account, index, addr)
+			} else {
+				Self::insert_link(ChainLinks(chain_id), account, index, addr)
+			}
+		}
+
+		/// The weight of linking `addr` of the chain `chain_id` with `link`
+		fn link_weight(chain_id: ChainId, addr: &[u8]) -> Weight {
+			if chain_id == EthVerifier::<T>::CHAIN_ID {
+				T::WeightInfo::link_eth(T::MaxEthLinks::get())
+			} else if chain_id == BtcVerifier::<T>::CHAIN_ID {
+				Self::link_btc_weight(addr)
+			} else {
+				T::WeightInfo::link(T::MaxChainLinks::get()).saturating_add(T::LinkVerifiers::weight(chain_id))
+			}
+		}
+
+		/// The weight of unlinking an address of the chain `chain_id` with `unlink`
+		fn unlink_weight(chain_id: ChainId) -> Weight {
+			if chain_id == EthVerifier::<T>::CHAIN_ID {
+				T::WeightInfo::unlink_eth(T::MaxEthLinks::get())
+			} else if chain_id == BtcVerifier::<T>::CHAIN_ID {
+				T::WeightInfo::unlink_btc(T::MaxBtcLinks::get())
+			} else {
+				T::WeightInfo::unlink(T::MaxChainLinks::get())
+			}
+		}
+
+		/// Verify the eth_sign signature of the link message of `account` by `addr_expected` with `EthVerifier`
+		fn verify_eth_link(
+			account: &T::AccountId,
+			addr_expected: [u8; 20],
+			expiring_block_number: T::BlockNumber,
+			r: [u8; 32],
+			s: [u8; 32],
+			v: u64,
+		) -> Result<[u8; 20], Error<T>> {
+			let recovery_id = util_eth::recovery_id(v).ok_or(Error::<T>::EcdsaRecoverFailure)?;
+			let signature = verifiers::join_signature(r, s, recovery_id);
+
+			Self::verify_link(account, &addr_expected, expiring_block_number,
+				|bytes| EthVerifier::<T>::recover_signer(&addr_expected, bytes, &signature))?;
+
+			Ok(addr_expected)
+		}
 
+		/// Verify the signature of the link message of `account` by the btc address `addr_expected` with `BtcVerifier`
+		fn verify_btc_link(
+			account: &T::AccountId,
+			addr_expected: &[u8],
+			expiring_block_number: T::BlockNumber,
+			r: [u8; 32],
+			s: [u8; 32],
+			v: u8,
+		) -> Result<Vec<u8>, Error<T>> {
+			let signature = verifiers::join_signature(r, s, v);
+
+			Self::verify_link(account, addr_expected, expiring_block_number,
+				|bytes| BtcVerifier::<T>::recover_signer(addr_expected, bytes, &signature))
+		}
+
+		/// Ensure `addr` is an address of the UTXO chain described by `params`, from its prefix
+		pub(crate) fn validate_utxo_addr(params: &UtxoChainParams, addr: &[u8]) -> Result<(), Error<T>> {
+			Self::utxo_addr_type(params, addr)?;
+			Ok(())
+		}
+
+		/// Recover the address of the UTXO chain described by `params` which signed `bytes`,
+		/// with the signature of the type of `addr_expected`, see `utxo_addr_from_sig`
+		pub(crate) fn utxo_addr_from_signature(
+			params: &UtxoChainParams,
+			addr_expected: &[u8],
+			bytes: &[u8],
+			r: [u8; 32],
+			s: [u8; 32],
+			v: u8,
+		) -> Result<Vec<u8>, Error<T>> {
+			let addr_type = Self::utxo_addr_type(params, addr_expected)?;
+			Self::utxo_addr_from_sig(params, addr_type, addr_expected, bytes, r, s, v)
+		}
+
+		/// The type of `addr`, an address of the UTXO chain described by `params`, from its prefix
+		fn utxo_addr_type(params: &UtxoChainParams, addr: &[u8]) -> Result<BTCAddrType, Error<T>> {
 			// TODO: we may enlarge this 2
-			if addr_expected.len() < 2 {
+			if addr.len() < 2 {
 				Err(Error::<T>::InvalidBTCAddressLength)?
 			}
 
-			let addr_type = if addr_expected[0] == b'1' {
-				BTCAddrType::Legacy
-			} else if addr_expected[0] == b'b' && addr_expected[1] == b'c' { // TODO: a better way?
-				BTCAddrType::Segwit
+			// bech32 addresses are `hrp` followed by the separator `1` and the witness version
+			let is_bech32 = |version: &[u8]| params.bech32_hrp.map_or(false, |hrp|
+				addr.starts_with(hrp) && addr[hrp.len()..].starts_with(version));
+
+			if is_bech32(b"1p") {
+				ensure!(params.taproot, Error::<T>::InvalidBTCAddress);
+				Ok(BTCAddrType::Taproot)
+			} else if is_bech32(b"1") {
+				Ok(BTCAddrType::Segwit)
+			} else if params.p2pkh_prefixes.contains(&addr[0]) {
+				Ok(BTCAddrType::Legacy)
+			} else if params.p2sh_version.is_some() && params.p2sh_prefixes.contains(&addr[0]) {
+				Ok(BTCAddrType::NestedSegwit)
 			} else {
-				Err(Error::<T>::InvalidBTCAddress)?
+				Err(Error::<T>::InvalidBTCAddress)
+			}
+		}
+
+		/// The weight of linking the btc address `addr`, whose type selects how its signature is verified
+		fn link_btc_weight(addr: &[u8]) -> Weight {
+			match Self::utxo_addr_type(&T::BitcoinNetwork::get().params(), addr) {
+				Ok(BTCAddrType::Taproot) => T::WeightInfo::link_btc_taproot(T::MaxBtcLinks::get()),
+				_ => T::WeightInfo::link_btc(T::MaxBtcLinks::get()),
+			}
+		}
+
+		/// Recover the address of `addr_type` which signed `bytes`, with a BIP-322 signature for
+		/// Taproot addresses and a BIP-137 signature otherwise
+		fn utxo_addr_from_sig(
+			params: &UtxoChainParams,
+			addr_type: BTCAddrType,
+			addr_expected: &[u8],
+			bytes: &[u8],
+			r: [u8; 32],
+			s: [u8; 32],
+			v: u8,
+		) -> Result<Vec<u8>, Error<T>> {
+			match addr_type {
+				BTCAddrType::Taproot => Self::p2tr_addr_from_bip322_sig(params, addr_expected, bytes, r, s, v),
+				_ => Self::btc_addr_from_bip137_sig(params, addr_type, bytes, r, s, v),
+			}
+		}
+
+		/// Ensure `origin` may link addresses to `account` under `T::LinkPolicy`
+		fn ensure_link_authorized(origin: &T::AccountId, account: &T::AccountId) -> Result<(), Error<T>> {
+			let authorized = match T::LinkPolicy::get() {
+				LinkPolicy::SelfOnly => origin == account,
+				LinkPolicy::SelfOrProxy => origin == account || T::LinkDelegates::is_delegate(account, origin),
+				LinkPolicy::Anyone => true,
 			};
+			ensure!(authorized, Error::<T>::UnauthorizedOrigin);
+			Ok(())
+		}
 
-			let mut bytes = b"Link Litentry: ".encode();
-			let mut account_vec = account.encode();
-			let mut expiring_block_number_vec = expiring_block_number.encode();
+		/// Whether `signature` is the signature of `signer` over `message`, raw or wrapped in
+		/// `<Bytes>..</Bytes>` by polkadot.js signers
+		fn polkadot_signature_valid(signature: &T::Signature, message: &[u8], signer: &T::AccountId) -> bool {
+			let mut wrapped = b"<Bytes>".to_vec();
+			wrapped.extend_from_slice(message);
+			wrapped.extend_from_slice(b"</Bytes>");
 
-			bytes.append(&mut account_vec);
-			bytes.append(&mut expiring_block_number_vec);
+			signature.verify(message, signer) || signature.verify(&wrapped[..], signer)
+		}
+
+		/// Ensure an unsigned link of `addr_expected` to `account` at slot `index` is authorised under
+		/// `T::LinkPolicy`: by the link signature alone under `LinkPolicy::Anyone`, otherwise by
+		/// `account_signature`, which must be the signature of `account` over `unsigned_link_message`.
+		fn ensure_unsigned_link_authorized(
+			account: &T::AccountId,
+			index: u32,
+			addr_expected: &[u8],
+			expiring_block_number: T::BlockNumber,
+			account_signature: &Option<T::Signature>,
+		) -> Result<(), Error<T>> {
+			match account_signature {
+				Some(signature) => {
+					let bytes = Self::unsigned_link_message(account, index, addr_expected, expiring_block_number);
+					ensure!(Self::polkadot_signature_valid(signature, &bytes, account), Error::<T>::InvalidPolkadotSignature);
+				},
+				None => ensure!(T::LinkPolicy::get() == LinkPolicy::Anyone, Error::<T>::UnauthorizedOrigin),
+			}
+			Ok(())
+		}
+
+		/// Ensure `account` exists, its last unsigned link is at least `UnsignedLinkInterval` blocks old
+		/// and the block has less than `MaxUnsignedLinksPerBlock` unsigned links
+		fn ensure_unsigned_link_allowed(account: &T::AccountId) -> Result<(), Error<T>> {
+			ensure!(<frame_system::Pallet<T>>::providers(account) > 0, Error::<T>::UnknownAccount);
+			if let Some(last) = Self::last_unsigned_link(account) {
+				ensure!(<frame_system::Pallet<T>>::block_number() >= last.saturating_add(T::UnsignedLinkInterval::get()),
+					Error::<T>::UnsignedLinkTooSoon);
+			}
+			ensure!(Self::unsigned_links_in_block() < T::MaxUnsignedLinksPerBlock::get(), Error::<T>::TooManyUnsignedLinks);
+			Ok(())
+		}
+
+		/// The number of unsigned links in the current block
+		pub fn unsigned_links_in_block() -> u32 {
+			match <UnsignedLinkCount<T>>::get() {
+				Some((block_number, count)) if block_number == <frame_system::Pallet<T>>::block_number() => count,
+				_ => 0,
+			}
+		}
+
+		/// Record an unsigned link of `account` in the current block
+		fn note_unsigned_link(account: &T::AccountId) {
+			let block_number = <frame_system::Pallet<T>>::block_number();
+			<LastUnsignedLink<T>>::insert(account, block_number);
+			<UnsignedLinkCount<T>>::put((block_number, Self::unsigned_links_in_block() + 1));
+		}
 
-			// TODO: seems btc uses sha256???
-			let hash = sp_io::hashing::keccak_256(&bytes);
+		/// The reason an unsigned link transaction is rejected from the pool
+		fn invalid_unsigned_link(error: Error<T>) -> InvalidTransaction {
+			match error {
+				Error::<T>::LinkRequestExpired => InvalidTransaction::Stale,
+				Error::<T>::UnsignedLinkTooSoon
+				| Error::<T>::TooManyUnsignedLinks => InvalidTransaction::ExhaustsResources,
+				Error::<T>::EcdsaRecoverFailure
+				| Error::<T>::UnexpectedAddress
+				| Error::<T>::MalleableEthSignature
+				| Error::<T>::InvalidBTCSignatureHeader
+				| Error::<T>::SchnorrVerifyFailure
+				| Error::<T>::InvalidPolkadotSignature => InvalidTransaction::BadProof,
+				_ => InvalidTransaction::Call,
+			}
+		}
+
+		/// Recover the eth address which signed `hash`.
+		/// `v` may be the raw recovery id, 27/28 or an EIP-155 value, and high-`s` signatures are rejected.
+		pub(crate) fn eth_addr_from_sig(hash: [u8; 32], r: [u8; 32], s: [u8; 32], v: u64) -> Result<[u8; 20], Error<T>> {
+			let recovery_id = util_eth::recovery_id(v).ok_or(Error::<T>::EcdsaRecoverFailure)?;
+			ensure!(!util_eth::is_high_s(&s), Error::<T>::MalleableEthSignature);
 
 			let mut msg = [0u8; 32];
 			let mut sig = [0u8; 65];
@@ -196,96 +1329,261 @@ pub mod pallet {
 			msg[..32].copy_from_slice(&hash[..32]);
 			sig[..32].copy_from_slice(&r[..32]);
 			sig[32..64].copy_from_slice(&s[..32]);
-			sig[64] = v;
+			sig[64] = recovery_id;
 
-			let pk = secp256k1_ecdsa_recover_compressed(&sig, &msg)
-			.map_err(|_| Error::<T>::EcdsaRecoverFailure)?;
+			util_eth::addr_from_sig(msg, sig)
+				.map_err(|_| Error::<T>::EcdsaRecoverFailure)
+		}
 
-			let addr = match addr_type {
-				BTCAddrType::Legacy => {
-					btc::legacy::btc_addr_from_pk(&pk).to_base58()
-				},
-				// Native P2WPKH is a scriptPubKey of 22 bytes.
-				// It starts with a OP_0, followed by a canonical push of the keyhash (i.e. 0x0014{20-byte keyhash})
-				// keyhash is RIPEMD160(SHA256) of a compressed public key
-				// https://bitcoincore.org/en/segwit_wallet_dev/
-				BTCAddrType::Segwit => {
-					let pk_hash = btc::legacy::hash160(&pk);
-					let mut pk = [0u8; 22];
-					pk[0] = 0;
-					pk[1] = 20;
-					pk[2..].copy_from_slice(&pk_hash);
-					let wp = WitnessProgram::from_scriptpubkey(&pk.to_vec()).map_err(|_| Error::<T>::InvalidBTCAddress)?;
-					wp.to_address(b"bc".to_vec()).map_err(|_| Error::<T>::InvalidBTCAddress)?
-				}
-			};
+		/// Link the verified address `addr` to `account` at slot `index` of the links `store`
+		fn insert_link<S: LinkStore<T>>(store: S, account: T::AccountId, index: u32, addr: S::Address) -> DispatchResultWithPostInfo {
+			let deposit = store.deposit();
+			Self::insert_link_with_deposit(store, account, index, addr, deposit)
+		}
 
-			ensure!(addr == addr_expected, Error::<T>::UnexpectedAddress);
+		/// Link `addr` like `insert_link`, reserving `deposit` if it takes a new slot
+		fn insert_link_with_deposit<S: LinkStore<T>>(
+			store: S,
+			account: T::AccountId,
+			index: u32,
+			addr: S::Address,
+			deposit: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_linkable(&store, &addr, &account)?;
 
 			let index = index as usize;
-			let mut addrs = Self::btc_addresses(&account);
-			// NOTE: allow linking `MAX_BTC_LINKS` btc addresses.
-			if (index >= addrs.len()) && (addrs.len() != MAX_BTC_LINKS) {
+			let mut addrs = store.addresses(&account);
+			let linked = addrs.len();
+			let max_links = store.max_links() as usize;
+			ensure!(linked <= max_links, Error::<T>::TooManyLinks);
+			// NOTE: allow linking `max_links` addresses.
+			let (index, replaced) = if (index >= addrs.len()) && (addrs.len() != max_links) {
 				addrs.push(addr.clone());
-			} else if (index >= addrs.len()) && (addrs.len() == MAX_BTC_LINKS) {
-				addrs[MAX_BTC_LINKS - 1] = addr.clone();
+				(addrs.len() - 1, None)
+			} else if (index >= addrs.len()) && (addrs.len() == max_links) {
+				(max_links - 1, Some(mem::replace(&mut addrs[max_links - 1], addr.clone())))
 			} else {
-				addrs[index] = addr.clone();
+				(index, Some(mem::replace(&mut addrs[index], addr.clone())))
+			};
+			ensure!(!addrs.iter().enumerate().any(|(i, linked)| i != index && *linked == addr), Error::<T>::DuplicateLink);
+
+			// a replaced slot keeps its deposit
+			if replaced.is_none() {
+				if !deposit.is_zero() {
+					Self::reserve_link_deposit(&account, deposit)?;
+				}
+				let mut deposits = Self::slot_deposits(&account, store.chain());
+				deposits.resize(linked, Zero::zero());
+				deposits.push(deposit);
+				<SlotDeposits<T>>::insert(&account, store.chain(), deposits);
+			}
+			if let Some(replaced) = &replaced {
+				if !addrs.contains(replaced) {
+					store.remove_owner(replaced, &account);
+					T::OnLinkChanged::on_unlinked(&account, &store.external(replaced));
+				}
 			}
+			store.insert_owner(&addr, &account);
 
-			<BitcoinLink<T>>::insert(account.clone(), addrs);
-			Self::deposit_event(Event::BtcAddressLinked(account, addr));
+			store.set_addresses(&account, addrs)?;
+			<LinkNonce<T>>::mutate(&account, |nonce| *nonce = nonce.wrapping_add(1));
+			T::OnLinkChanged::on_linked(&account, &store.external(&addr));
+			Self::deposit_event(store.linked_event(account, addr, index as u32, replaced));
 
 			Ok(().into())
-
 		}
 
-		#[pallet::weight(T::WeightInfo::link_polkadot())]
-		pub fn link_polkadot(
-			origin: OriginFor<T>,
-			account: T::AccountId,
-			index: u32,
-		) -> DispatchResultWithPostInfo {
+		/// Unlink the address selected by `slot` from `account` in the links `store`.
+		/// Addresses in later slots are shifted down by one, so slot indexes stay contiguous.
+		fn unlink_address<S: LinkStore<T>>(store: S, account: T::AccountId, slot: LinkSlot<S::Address>) -> DispatchResultWithPostInfo {
+			let mut addrs = store.addresses(&account);
+			let (index, addr) = Self::take_slot(&mut addrs, slot)?;
 
-			let origin = ensure_signed(origin)?;
-
-			// TODO: charge some fee
+			if !addrs.contains(&addr) {
+				store.remove_owner(&addr, &account);
+			}
+			store.set_addresses(&account, addrs)?;
 
-			<PolkadotPending<T>>::insert(origin, (account, index));
+			let mut deposits = Self::slot_deposits(&account, store.chain());
+			let deposit = if index < deposits.len() { deposits.remove(index) } else { Zero::zero() };
+			if deposits.is_empty() {
+				<SlotDeposits<T>>::remove(&account, store.chain());
+			} else {
+				<SlotDeposits<T>>::insert(&account, store.chain(), deposits);
+			}
+			Self::unreserve_link_deposit(&account, deposit);
+			T::OnLinkChanged::on_unlinked(&account, &store.external(&addr));
+			Self::deposit_event(store.unlinked_event(account, addr));
 
 			Ok(().into())
-
 		}
 
-		#[pallet::weight(T::WeightInfo::accept_polkadot())]
-		pub fn accept_polkadot(
-			origin: OriginFor<T>,
-			account: T::AccountId,
-		) -> DispatchResultWithPostInfo {
+		/// Reserve the deposit of a new link from `account`
+		fn reserve_link_deposit(account: &T::AccountId, deposit: BalanceOf<T>) -> DispatchResult {
+			T::Currency::reserve(account, deposit)?;
+			<LinkDeposit<T>>::mutate(account, |held| *held = held.saturating_add(deposit));
+			Ok(())
+		}
 
-			let origin = ensure_signed(origin)?;
+		/// Unreserve the deposit recorded for a removed slot from `account`, at most the held deposit.
+		fn unreserve_link_deposit(account: &T::AccountId, deposit: BalanceOf<T>) {
+			let held = Self::link_deposit(account);
+			let deposit = deposit.min(held);
+			let _ = T::Currency::unreserve(account, deposit);
+			if held == deposit {
+				<LinkDeposit<T>>::remove(account);
+			} else {
+				<LinkDeposit<T>>::insert(account, held - deposit);
+			}
+		}
 
-			let (target, index) = Self::polkadot_pending(&account);
-			ensure!(target == origin, Error::<T>::WrongPendingRequest);
-			<PolkadotPending<T>>::remove(&origin);
+		/// The slot index and the expiring block number of the pending request of `origin` to link `target`
+		pub fn polkadot_pending(origin: &T::AccountId, target: &T::AccountId) -> Option<(u32, T::BlockNumber)> {
+			Self::polkadot_pending_links(origin).into_iter()
+				.find(|request| request.target == *target)
+				.map(|request| (request.index, request.expiring_block_number))
+		}
 
-			let index = index as usize;
-			let mut addrs = Self::polkadot_addresses(&target);
-			// NOTE: allow linking `MAX_POLKADOT_LINKS` polkadot addresses.
-			if (index >= addrs.len()) && (addrs.len() != MAX_POLKADOT_LINKS) {
-				addrs.push(origin.clone());
-			} else if (index >= addrs.len()) && (addrs.len() == MAX_POLKADOT_LINKS) {
-				addrs[MAX_POLKADOT_LINKS - 1] = origin.clone();
+		/// Remove the pending request of `origin` to link `target`, return its deposit to `origin` and return it
+		fn remove_polkadot_pending(origin: &T::AccountId, target: &T::AccountId) -> Option<PolkadotPendingLinkOf<T>> {
+			let mut pending = Self::polkadot_pending_links(origin);
+			let request = pending.remove(pending.iter().position(|request| request.target == *target)?);
+			if pending.is_empty() {
+				<PolkadotPending<T>>::remove(origin);
 			} else {
-				addrs[index] = origin.clone();
+				<PolkadotPending<T>>::insert(origin, pending);
 			}
+			<PolkadotPendingExpiry<T>>::remove(request.expiring_block_number, (origin.clone(), target.clone()));
+			let _ = T::Currency::unreserve(origin, request.deposit);
+			Some(request)
+		}
 
-			<PolkadotLink<T>>::insert(account.clone(), addrs);
-			Self::deposit_event(Event::PolkadotAddressLinked(account, origin));
+		/// Salt of the EIP-712 domain of `link_eth_typed`, `keccak256(genesis_hash ++ pallet_index)`
+		pub fn eth_typed_data_salt() -> [u8; 32] {
+			let mut bytes = <frame_system::Pallet<T>>::block_hash(T::BlockNumber::from(0u32)).encode();
+			bytes.push(<Self as PalletInfoAccess>::index() as u8);
+			sp_io::hashing::keccak_256(&bytes)
+		}
 
-			Ok(().into())
+		/// Recover the address of `addr_type` from a BIP-137 signature of a signed message of the chain
+		fn btc_addr_from_bip137_sig(
+			params: &UtxoChainParams,
+			addr_type: BTCAddrType,
+			bytes: &[u8],
+			r: [u8; 32],
+			s: [u8; 32],
+			v: u8,
+		) -> Result<Vec<u8>, Error<T>> {
+			let (recovery_id, sig_type) = btc::message::decode_header(v)
+				.map_err(|_| Error::<T>::InvalidBTCSignatureHeader)?;
+
+			let msg = btc::message::signed_msg_hash(params.message_magic, bytes);
+			let mut sig = [0u8; 65];
+
+			sig[..32].copy_from_slice(&r[..32]);
+			sig[32..64].copy_from_slice(&s[..32]);
+			sig[64] = recovery_id;
+
+			let addr = match (addr_type, sig_type) {
+				(BTCAddrType::Legacy, SigType::P2PKHUncompressed) => {
+					let pk_raw = secp256k1_ecdsa_recover(&sig, &msg)
+						.map_err(|_| Error::<T>::EcdsaRecoverFailure)?;
+					let mut pk = [0u8; 65];
+					pk[0] = 4;
+					pk[1..].copy_from_slice(&pk_raw);
+					btc::legacy::btc_addr_from_pk(&pk, params.p2pkh_version).to_base58()
+				},
+				(BTCAddrType::Legacy, SigType::P2PKHCompressed) => {
+					let pk = secp256k1_ecdsa_recover_compressed(&sig, &msg)
+						.map_err(|_| Error::<T>::EcdsaRecoverFailure)?;
+					btc::legacy::btc_addr_from_pk(&pk, params.p2pkh_version).to_base58()
+				},
+				// P2SH-P2WPKH wraps the P2WPKH witness program of a compressed public key in a P2SH redeem script
+				(BTCAddrType::NestedSegwit, SigType::P2SHP2WPKH) | (BTCAddrType::NestedSegwit, SigType::P2PKHCompressed) => {
+					let pk = secp256k1_ecdsa_recover_compressed(&sig, &msg)
+						.map_err(|_| Error::<T>::EcdsaRecoverFailure)?;
+					let version = params.p2sh_version.ok_or(Error::<T>::InvalidBTCAddress)?;
+					btc::legacy::p2sh_p2wpkh_addr_from_pk(&pk, version).to_base58()
+				},
+				// Native P2WPKH is a scriptPubKey of 22 bytes.
+				// It starts with a OP_0, followed by a canonical push of the keyhash (i.e. 0x0014{20-byte keyhash})
+				// keyhash is RIPEMD160(SHA256) of a compressed public key
+				// https://bitcoincore.org/en/segwit_wallet_dev/
+				// NOTE: Electrum signs segwit addresses with the compressed P2PKH header
+				(BTCAddrType::Segwit, SigType::P2WPKH) | (BTCAddrType::Segwit, SigType::P2PKHCompressed) => {
+					let pk = secp256k1_ecdsa_recover_compressed(&sig, &msg)
+						.map_err(|_| Error::<T>::EcdsaRecoverFailure)?;
+					let pk_hash = btc::legacy::hash160(&pk);
+					let mut pk = [0u8; 22];
+					pk[0] = 0;
+					pk[1] = 20;
+					pk[2..].copy_from_slice(&pk_hash);
+					let wp = WitnessProgram::from_scriptpubkey(&pk.to_vec()).map_err(|_| Error::<T>::InvalidBTCAddress)?;
+					let hrp = params.bech32_hrp.ok_or(Error::<T>::InvalidBTCAddress)?;
+					wp.to_address(hrp.to_vec()).map_err(|_| Error::<T>::InvalidBTCAddress)?
+				},
+				_ => Err(Error::<T>::InvalidBTCSignatureHeader)?,
+			};
+
+			Ok(addr)
+		}
+
+		/// Verify a BIP-322 simple signature of a Taproot key path spend and return the canonical address.
+		/// `v` is the sighash type, 0 (SIGHASH_DEFAULT) for a 64-byte signature or 1 (SIGHASH_ALL).
+		fn p2tr_addr_from_bip322_sig(
+			params: &UtxoChainParams,
+			addr_expected: &[u8],
+			bytes: &[u8],
+			r: [u8; 32],
+			s: [u8; 32],
+			v: u8,
+		) -> Result<Vec<u8>, Error<T>> {
+			let hrp = params.bech32_hrp.ok_or(Error::<T>::InvalidBTCAddress)?;
+			let wp = WitnessProgram::from_address(hrp, addr_expected).map_err(|_| Error::<T>::InvalidBTCAddress)?;
+			ensure!(wp.version == 1 && wp.program.len() == 32, Error::<T>::InvalidBTCAddress);
+
+			let mut output_key = [0u8; 32];
+			output_key.copy_from_slice(&wp.program);
+
+			let mut sig = r.to_vec();
+			sig.extend_from_slice(&s);
+			if v != 0 {
+				sig.push(v);
+			}
+
+			ensure!(btc::bip322::verify_p2tr(bytes, &output_key, &sig), Error::<T>::SchnorrVerifyFailure);
+
+			wp.to_address(hrp.to_vec()).map_err(|_| Error::<T>::InvalidBTCAddress)
+		}
+
+		/// Remove the address selected by `slot` from `addrs` and return its slot index and it.
+		fn take_slot<A: PartialEq>(addrs: &mut Vec<A>, slot: LinkSlot<A>) -> Result<(usize, A), Error<T>> {
+			let index = match slot {
+				LinkSlot::Index(index) => index as usize,
+				LinkSlot::Address(addr) => addrs.iter().position(|a| *a == addr)
+					.ok_or(Error::<T>::LinkNotFound)?,
+			};
+			ensure!(index < addrs.len(), Error::<T>::LinkNotFound);
+
+			Ok((index, addrs.remove(index)))
 		}
 	}
 }
 
+impl<T: Config> LinkedAccountsProvider<T::AccountId> for Pallet<T> {
+	fn eth_addresses(account: &T::AccountId) -> Vec<[u8; 20]> {
+		Self::eth_addresses(account)
+	}
+
+	fn btc_addresses(account: &T::AccountId) -> Vec<Vec<u8>> {
+		Self::btc_addresses(account)
+	}
 
+	fn polkadot_addresses(account: &T::AccountId) -> Vec<T::AccountId> {
+		Self::polkadot_addresses(account)
+	}
+
+	fn chain_addresses(chain_id: ChainId, account: &T::AccountId) -> Vec<Vec<u8>> {
+		Self::chain_addresses(chain_id, account)
+	}
+}
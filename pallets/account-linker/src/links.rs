@@ -0,0 +1,176 @@
+//! The storage of the links of each chain, shared by the link and unlink calls of the chains.
+
+use crate::{BalanceOf, ChainId, ExternalAddress};
+use crate::pallet::*;
+use frame_support::{storage::bounded_vec::BoundedVec, traits::Get};
+use sp_std::{convert::TryFrom, prelude::*};
+
+/// The addresses of a chain linked to each account, in slots, with their reverse index
+pub(crate) trait LinkStore<T: Config> {
+	/// The address of the chain as it is stored
+	type Address: Clone + PartialEq;
+
+	/// The chain keying the deposits of the slots of the store
+	fn chain(&self) -> LinkChain;
+
+	/// The maximum number of addresses linked to an account
+	fn max_links(&self) -> u32;
+
+	/// The deposit reserved for each linked address
+	fn deposit(&self) -> BalanceOf<T>;
+
+	/// The addresses linked to `account`, by slot
+	fn addresses(&self, account: &T::AccountId) -> Vec<Self::Address>;
+
+	/// Store the addresses linked to `account`, removing its entry if there are none
+	fn set_addresses(&self, account: &T::AccountId, addrs: Vec<Self::Address>) -> Result<(), Error<T>>;
+
+	/// Whether `addr` is linked to an account other than `account`, reading the owners lazily
+	fn linked_to_other(&self, addr: &Self::Address, account: &T::AccountId) -> bool;
+
+	/// Index `addr` as linked to `account`
+	fn insert_owner(&self, addr: &Self::Address, account: &T::AccountId);
+
+	/// Remove `addr` from the index of the addresses linked to `account`
+	fn remove_owner(&self, addr: &Self::Address, account: &T::AccountId);
+
+	/// `addr` as an address of any chain
+	fn external(&self, addr: &Self::Address) -> ExternalAddress<T::AccountId>;
+
+	/// The event of `addr` linked to `account` at slot `index`
+	fn linked_event(&self, account: T::AccountId, addr: Self::Address, index: u32, replaced: Option<Self::Address>) -> Event<T>;
+
+	/// The event of `addr` unlinked from `account`
+	fn unlinked_event(&self, account: T::AccountId, addr: Self::Address) -> Event<T>;
+}
+
+/// Implement `LinkStore` for the links of a chain stored in a map of addresses by account and a
+/// double map of accounts by address. `$encode` encodes addresses in the events of the chain.
+macro_rules! impl_link_store {
+	(
+		$store:ident, $address:ty, $links:ident, $owners:ident, $max_links:ident, $deposit:ident,
+		$external:ident, $linked:ident, $unlinked:ident, |$addr:ident| $encode:expr
+	) => {
+		pub(crate) struct $store;
+
+		impl<T: Config> LinkStore<T> for $store {
+			type Address = $address;
+
+			fn chain(&self) -> LinkChain {
+				LinkChain::$external
+			}
+
+			fn max_links(&self) -> u32 {
+				T::$max_links::get()
+			}
+
+			fn deposit(&self) -> BalanceOf<T> {
+				T::$deposit::get()
+			}
+
+			fn addresses(&self, account: &T::AccountId) -> Vec<$address> {
+				<$links<T>>::get(account).into_inner()
+			}
+
+			fn set_addresses(&self, account: &T::AccountId, addrs: Vec<$address>) -> Result<(), Error<T>> {
+				if addrs.is_empty() {
+					<$links<T>>::remove(account);
+				} else {
+					let addrs = BoundedVec::<_, T::$max_links>::try_from(addrs).map_err(|_| Error::<T>::TooManyLinks)?;
+					<$links<T>>::insert(account, addrs);
+				}
+				Ok(())
+			}
+
+			fn linked_to_other(&self, addr: &$address, account: &T::AccountId) -> bool {
+				<$owners<T>>::iter_prefix(addr).any(|(owner, _)| owner != *account)
+			}
+
+			fn insert_owner(&self, addr: &$address, account: &T::AccountId) {
+				<$owners<T>>::insert(addr, account, ());
+			}
+
+			fn remove_owner(&self, addr: &$address, account: &T::AccountId) {
+				<$owners<T>>::remove(addr, account);
+			}
+
+			fn external(&self, addr: &$address) -> ExternalAddress<T::AccountId> {
+				ExternalAddress::$external(addr.clone())
+			}
+
+			fn linked_event(&self, account: T::AccountId, addr: $address, index: u32, replaced: Option<$address>) -> Event<T> {
+				let encode = |$addr: $address| $encode;
+				Event::$linked(account, encode(addr), index, replaced.map(encode))
+			}
+
+			fn unlinked_event(&self, account: T::AccountId, addr: $address) -> Event<T> {
+				let encode = |$addr: $address| $encode;
+				Event::$unlinked(account, encode(addr))
+			}
+		}
+	};
+}
+
+impl_link_store!(EthLinks, [u8; 20], EthereumLink, EthereumLinkOwner, MaxEthLinks, EthLinkDeposit,
+	Eth, EthAddressLinked, EthAddressUnlinked, |addr| addr.to_vec());
+impl_link_store!(BtcLinks, Vec<u8>, BitcoinLink, BitcoinLinkOwner, MaxBtcLinks, BtcLinkDeposit,
+	Btc, BtcAddressLinked, BtcAddressUnlinked, |addr| addr);
+impl_link_store!(PolkadotLinks, T::AccountId, PolkadotLink, PolkadotLinkOwner, MaxPolkadotLinks, PolkadotLinkDeposit,
+	Polkadot, PolkadotAddressLinked, PolkadotAddressUnlinked, |addr| addr);
+
+/// The links of the chain `.0` of `T::LinkVerifiers`, all chains sharing the same maps keyed by chain id
+pub(crate) struct ChainLinks(pub(crate) ChainId);
+
+impl<T: Config> LinkStore<T> for ChainLinks {
+	type Address = Vec<u8>;
+
+	fn chain(&self) -> LinkChain {
+		LinkChain::Chain(self.0)
+	}
+
+	fn max_links(&self) -> u32 {
+		T::MaxChainLinks::get()
+	}
+
+	fn deposit(&self) -> BalanceOf<T> {
+		T::ChainLinkDeposit::get()
+	}
+
+	fn addresses(&self, account: &T::AccountId) -> Vec<Vec<u8>> {
+		<ChainLink<T>>::get(account, self.0).into_inner()
+	}
+
+	fn set_addresses(&self, account: &T::AccountId, addrs: Vec<Vec<u8>>) -> Result<(), Error<T>> {
+		if addrs.is_empty() {
+			<ChainLink<T>>::remove(account, self.0);
+		} else {
+			let addrs = BoundedVec::<_, T::MaxChainLinks>::try_from(addrs).map_err(|_| Error::<T>::TooManyLinks)?;
+			<ChainLink<T>>::insert(account, self.0, addrs);
+		}
+		Ok(())
+	}
+
+	fn linked_to_other(&self, addr: &Vec<u8>, account: &T::AccountId) -> bool {
+		<ChainLinkOwner<T>>::iter_prefix((self.0, addr)).any(|(owner, _)| owner != *account)
+	}
+
+	fn insert_owner(&self, addr: &Vec<u8>, account: &T::AccountId) {
+		<ChainLinkOwner<T>>::insert((self.0, addr), account, ());
+	}
+
+	fn remove_owner(&self, addr: &Vec<u8>, account: &T::AccountId) {
+		<ChainLinkOwner<T>>::remove((self.0, addr), account);
+	}
+
+	fn external(&self, addr: &Vec<u8>) -> ExternalAddress<T::AccountId> {
+		ExternalAddress::Chain(self.0, addr.clone())
+	}
+
+	fn linked_event(&self, account: T::AccountId, addr: Vec<u8>, index: u32, replaced: Option<Vec<u8>>) -> Event<T> {
+		Event::AddressLinked(account, self.0, addr, index, replaced)
+	}
+
+	fn unlinked_event(&self, account: T::AccountId, addr: Vec<u8>) -> Event<T> {
+		Event::AddressUnlinked(account, self.0, addr)
+	}
+}
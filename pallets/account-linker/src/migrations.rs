@@ -0,0 +1,99 @@
+//! Storage migrations of the account linker pallet.
+
+use crate::*;
+use frame_support::{
+	storage::{bounded_vec::BoundedVec, migration::storage_key_iter},
+	traits::{Get, PalletInfoAccess},
+	weights::Weight,
+	Blake2_128Concat,
+};
+use sp_runtime::traits::Zero;
+use sp_std::{convert::TryFrom, prelude::*};
+
+/// Move the linked addresses of every chain into bounded vectors.
+/// Links beyond the configured limit of a chain are dropped from the last slots.
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	let mut translated: Weight = 0;
+
+	EthereumLink::<T>::translate::<Vec<[u8; 20]>, _>(|_, addrs| {
+		translated += 1;
+		bounded(addrs)
+	});
+	BitcoinLink::<T>::translate::<Vec<Vec<u8>>, _>(|_, addrs| {
+		translated += 1;
+		bounded(addrs)
+	});
+	PolkadotLink::<T>::translate::<Vec<T::AccountId>, _>(|_, addrs| {
+		translated += 1;
+		bounded(addrs)
+	});
+
+	StorageVersion::<T>::put(Releases::V2_0_0);
+	log::info!("account linker: migrated {} accounts to bounded links", translated);
+
+	T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+}
+
+/// Index the linked addresses of every chain by the accounts they are linked to.
+pub fn migrate_to_v3<T: Config>() -> Weight {
+	let mut reads: Weight = 0;
+	let mut writes: Weight = 0;
+
+	for (account, addrs) in EthereumLink::<T>::iter() {
+		reads += 1;
+		for addr in addrs.iter() {
+			writes += 1;
+			EthereumLinkOwner::<T>::insert(addr, &account, ());
+		}
+	}
+	for (account, addrs) in BitcoinLink::<T>::iter() {
+		reads += 1;
+		for addr in addrs.iter() {
+			writes += 1;
+			BitcoinLinkOwner::<T>::insert(addr, &account, ());
+		}
+	}
+	for (account, addrs) in PolkadotLink::<T>::iter() {
+		reads += 1;
+		for addr in addrs.iter() {
+			writes += 1;
+			PolkadotLinkOwner::<T>::insert(addr, &account, ());
+		}
+	}
+
+	StorageVersion::<T>::put(Releases::V3_0_0);
+	log::info!("account linker: indexed {} linked addresses", writes);
+
+	T::DbWeight::get().reads_writes(reads + 1, writes + 1)
+}
+
+/// Store the pending polkadot link requests per origin, with an expiring block number and a deposit.
+/// The single request of each origin is kept without a deposit and expires after the maximum expiring period.
+pub fn migrate_to_v4<T: Config>() -> Weight {
+	let expiring_block_number = <frame_system::Pallet<T>>::block_number()
+		+ T::BlockNumber::from(EXPIRING_BLOCK_NUMBER_MAX - 1);
+
+	let pending: Vec<(T::AccountId, (T::AccountId, u32))> = storage_key_iter::<_, _, Blake2_128Concat>(
+		<Pallet<T> as PalletInfoAccess>::name().as_bytes(),
+		b"PolkadotPending",
+	).drain().collect();
+
+	let migrated = pending.len() as Weight;
+	for (origin, (target, index)) in pending {
+		let request = PolkadotPendingLink { target: target.clone(), index, expiring_block_number, deposit: Zero::zero() };
+		if let Ok(requests) = BoundedVec::<_, T::MaxPendingPerOrigin>::try_from(vec![request]) {
+			PolkadotPending::<T>::insert(&origin, requests);
+			PolkadotPendingExpiry::<T>::insert(expiring_block_number, (origin, target), ());
+		}
+	}
+
+	StorageVersion::<T>::put(Releases::V4_0_0);
+	log::info!("account linker: migrated {} pending polkadot link requests", migrated);
+
+	T::DbWeight::get().reads_writes(migrated + 2, migrated * 3 + 1)
+}
+
+fn bounded<A, S: Get<u32>>(mut addrs: Vec<A>) -> Option<BoundedVec<A, S>> {
+	addrs.truncate(S::get() as usize);
+	BoundedVec::try_from(addrs).ok()
+}
@@ -1,17 +1,26 @@
 use frame_support::{
+	ensure,
 	parameter_types,
-	traits::{OnFinalize, OnInitialize},
+	traits::{Get, OnFinalize, OnInitialize},
+	weights::Weight,
 };
+use std::cell::RefCell;
 use frame_system as system;
 use crate as account_linker;
-use sp_core::H256;
+use sp_core::{ed25519, H256};
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup},
+	DispatchError,
 	AccountId32,
 	generic,
+	MultiSignature,
+	MultiSigner,
 };
 
-pub use crate::MAX_ETH_LINKS;
+pub use crate::{
+	BTCNetwork, BtcVerifier, ChainId, CosmosVerifier, DogeVerifier, EthVerifier, ExternalAddress, LinkDelegates,
+	LinkVerifier, LtcVerifier, OnLinkChanged, SolVerifier, TronVerifier, UtxoChainParams,
+};
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -24,13 +33,26 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		AccountLinker: account_linker::{Pallet, Call, Storage, Event<T>},
+		AccountLinker: account_linker::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
 parameter_types! {
 	pub const BlockHashCount: u32 = 250;
 	pub const SS58Prefix: u8 = 42;
+	pub const MaxEthLinks: u32 = 3;
+	pub const MaxBtcLinks: u32 = 3;
+	pub const MaxPolkadotLinks: u32 = 3;
+	pub const CosmosHrp: &'static [u8] = b"cosmos";
+	pub const MaxChainLinks: u32 = 3;
+	pub const MaxPendingPerOrigin: u32 = 3;
+	pub const LitecoinParams: UtxoChainParams = UtxoChainParams::LITECOIN;
+	pub const DogecoinParams: UtxoChainParams = UtxoChainParams::DOGECOIN;
+	pub const ExistentialDeposit: u64 = 1;
+	pub const UnsignedPriority: u64 = 100;
+	pub const UnsignedLinkInterval: u32 = 10;
+	pub const MaxUnsignedLinksPerBlock: u32 = 2;
 }
 
 impl system::Config for Test {
@@ -51,7 +73,7 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -59,15 +81,221 @@ impl system::Config for Test {
 	type OnSetCode = ();
 }
 
+impl pallet_balances::Config for Test {
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type MaxLocks = ();
+	type WeightInfo = ();
+}
+
+thread_local! {
+	static BITCOIN_NETWORK: RefCell<BTCNetwork> = RefCell::new(BTCNetwork::Mainnet);
+	static UNIQUE_LINKS: RefCell<bool> = RefCell::new(true);
+	static LINK_DEPOSIT: RefCell<u64> = RefCell::new(0);
+	static LINK_CHANGES: RefCell<Vec<LinkChange>> = RefCell::new(Vec::new());
+	static LINK_POLICY: RefCell<account_linker::LinkPolicy> = RefCell::new(account_linker::LinkPolicy::Anyone);
+	static LINK_DELEGATES: RefCell<Vec<(AccountId32, AccountId32)>> = RefCell::new(Vec::new());
+	static MAX_PENDING_EXPIRIES: RefCell<u32> = RefCell::new(2);
+}
+
+pub struct BitcoinNetwork;
+impl Get<BTCNetwork> for BitcoinNetwork {
+	fn get() -> BTCNetwork {
+		BITCOIN_NETWORK.with(|v| *v.borrow())
+	}
+}
+
+pub struct UniqueLinks;
+impl Get<bool> for UniqueLinks {
+	fn get() -> bool {
+		UNIQUE_LINKS.with(|v| *v.borrow())
+	}
+}
+
+pub fn set_unique_links(unique: bool) {
+	UNIQUE_LINKS.with(|v| *v.borrow_mut() = unique);
+}
+
+// The same deposit is reserved for links of every chain
+pub struct Deposit;
+impl Get<u64> for Deposit {
+	fn get() -> u64 {
+		LINK_DEPOSIT.with(|v| *v.borrow())
+	}
+}
+
+pub fn set_link_deposit(deposit: u64) {
+	LINK_DEPOSIT.with(|v| *v.borrow_mut() = deposit);
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum LinkChange {
+	Linked(AccountId32, ExternalAddress<AccountId32>),
+	Unlinked(AccountId32, ExternalAddress<AccountId32>),
+}
+
+// Records the link changes notified by the pallet
+pub struct LinkChanges;
+impl OnLinkChanged<AccountId32> for LinkChanges {
+	fn on_linked(account: &AccountId32, address: &ExternalAddress<AccountId32>) {
+		LINK_CHANGES.with(|v| v.borrow_mut().push(LinkChange::Linked(account.clone(), address.clone())));
+	}
+
+	fn on_unlinked(account: &AccountId32, address: &ExternalAddress<AccountId32>) {
+		LINK_CHANGES.with(|v| v.borrow_mut().push(LinkChange::Unlinked(account.clone(), address.clone())));
+	}
+}
+
+pub fn link_changes() -> Vec<LinkChange> {
+	LINK_CHANGES.with(|v| v.borrow_mut().drain(..).collect())
+}
+
+pub struct Policy;
+impl Get<account_linker::LinkPolicy> for Policy {
+	fn get() -> account_linker::LinkPolicy {
+		LINK_POLICY.with(|v| *v.borrow())
+	}
+}
+
+pub fn set_link_policy(policy: account_linker::LinkPolicy) {
+	LINK_POLICY.with(|v| *v.borrow_mut() = policy);
+}
+
+// Delegates of the accounts, set by the tests
+pub struct Delegates;
+impl LinkDelegates<AccountId32> for Delegates {
+	fn is_delegate(account: &AccountId32, delegate: &AccountId32) -> bool {
+		LINK_DELEGATES.with(|v| v.borrow().contains(&(account.clone(), delegate.clone())))
+	}
+}
+
+pub fn add_link_delegate(account: AccountId32, delegate: AccountId32) {
+	LINK_DELEGATES.with(|v| v.borrow_mut().push((account, delegate)));
+}
+
+pub struct MaxPendingExpiriesPerBlock;
+impl Get<u32> for MaxPendingExpiriesPerBlock {
+	fn get() -> u32 {
+		MAX_PENDING_EXPIRIES.with(|v| *v.borrow())
+	}
+}
+
+pub fn set_max_pending_expiries_per_block(max: u32) {
+	MAX_PENDING_EXPIRIES.with(|v| *v.borrow_mut() = max);
+}
+
+pub const INVALID_ADDRESS: DispatchError = DispatchError::Other("invalid address");
+pub const INVALID_SIGNATURE: DispatchError = DispatchError::Other("invalid signature");
+
+// A chain whose addresses are lowercase hex ed25519 public keys, signing the link message itself
+pub struct HexEd25519Verifier;
+impl LinkVerifier for HexEd25519Verifier {
+	const CHAIN_ID: ChainId = 1000;
+
+	type Error = &'static str;
+
+	fn validate_address(addr: &[u8]) -> Result<(), &'static str> {
+		ensure!(addr.len() == 64 && addr.iter().all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f')), "invalid address");
+		Ok(())
+	}
+
+	fn signed_message(link_message: &[u8]) -> Vec<u8> {
+		link_message.to_vec()
+	}
+
+	fn recover_address(addr_expected: &[u8], message: &[u8], signature: &[u8]) -> Result<Vec<u8>, &'static str> {
+		ensure!(signature.len() == 64, "invalid signature");
+		let pk = ed25519::Public::from_slice(&hex::decode(addr_expected).map_err(|_| "invalid address")?);
+		let sig = ed25519::Signature::from_slice(signature);
+		ensure!(sp_io::crypto::ed25519_verify(&sig, message, &pk), "invalid signature");
+		Ok(addr_expected.to_vec())
+	}
+
+	fn weight() -> Weight {
+		1_000
+	}
+}
+
+// The same chain as `HexEd25519Verifier`, whose wallets prefix the signed message
+pub struct PrefixedHexEd25519Verifier;
+impl LinkVerifier for PrefixedHexEd25519Verifier {
+	const CHAIN_ID: ChainId = 1001;
+
+	type Error = &'static str;
+
+	fn validate_address(addr: &[u8]) -> Result<(), &'static str> {
+		HexEd25519Verifier::validate_address(addr)
+	}
+
+	fn signed_message(link_message: &[u8]) -> Vec<u8> {
+		let mut message = b"\x19Test Signed Message:\n".to_vec();
+		message.extend_from_slice(link_message);
+		message
+	}
+
+	fn recover_address(addr_expected: &[u8], message: &[u8], signature: &[u8]) -> Result<Vec<u8>, &'static str> {
+		HexEd25519Verifier::recover_address(addr_expected, message, signature)
+	}
+
+	fn weight() -> Weight {
+		2_000
+	}
+}
+
 impl account_linker::Config for Test {
 	type Event = Event;
 	type WeightInfo = ();
+	type BitcoinNetwork = BitcoinNetwork;
+	type MaxEthLinks = MaxEthLinks;
+	type MaxBtcLinks = MaxBtcLinks;
+	type MaxPolkadotLinks = MaxPolkadotLinks;
+	type UniqueLinks = UniqueLinks;
+	type Signature = MultiSignature;
+	type Signer = MultiSigner;
+	type Currency = Balances;
+	type EthLinkDeposit = Deposit;
+	type BtcLinkDeposit = Deposit;
+	type PolkadotLinkDeposit = Deposit;
+	type OnLinkChanged = LinkChanges;
+	type UnsignedPriority = UnsignedPriority;
+	type UnsignedLinkInterval = UnsignedLinkInterval;
+	type LinkPolicy = Policy;
+	type LinkDelegates = Delegates;
+	type LinkVerifiers = (
+		HexEd25519Verifier,
+		PrefixedHexEd25519Verifier,
+		SolVerifier<Test>,
+		CosmosVerifier<Test, CosmosHrp>,
+		TronVerifier<Test>,
+		LtcVerifier<Test, LitecoinParams>,
+		DogeVerifier<Test, DogecoinParams>,
+	);
+	type MaxChainLinks = MaxChainLinks;
+	type ChainLinkDeposit = Deposit;
+	type MaxPendingPerOrigin = MaxPendingPerOrigin;
+	type MaxPendingExpiriesPerBlock = MaxPendingExpiriesPerBlock;
+	type PolkadotRequestDeposit = Deposit;
+	type MaxUnsignedLinksPerBlock = MaxUnsignedLinksPerBlock;
 }
 
 pub type AccountLinkerError = account_linker::Error<Test>;
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
+	new_test_ext_with_network(BTCNetwork::Mainnet)
+}
+
+pub fn new_test_ext_with_network(network: BTCNetwork) -> sp_io::TestExternalities {
+	BITCOIN_NETWORK.with(|v| *v.borrow_mut() = network);
+	set_unique_links(true);
+	set_link_deposit(0);
+	set_link_policy(account_linker::LinkPolicy::Anyone);
+	LINK_DELEGATES.with(|v| v.borrow_mut().clear());
+	set_max_pending_expiries_per_block(2);
+	link_changes();
 	system::GenesisConfig::default()
 		.build_storage::<Test>()
 		.unwrap()
@@ -90,4 +318,103 @@ pub fn events() -> Vec<Event> {
 	System::reset_events();
 
 	evt
-}
\ No newline at end of file
+}
+/// A mock runtime with `u64` block numbers, which lengthens the link message.
+pub mod block_number_u64 {
+	use super::*;
+
+	type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+	type Block = frame_system::mocking::MockBlock<Test>;
+
+	frame_support::construct_runtime!(
+		pub enum Test where
+			Block = Block,
+			NodeBlock = Block,
+			UncheckedExtrinsic = UncheckedExtrinsic,
+		{
+			System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+			AccountLinker: account_linker::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+			Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		}
+	);
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const UnsignedLinkInterval: u64 = 10;
+	}
+
+	impl system::Config for Test {
+		type BaseCallFilter = ();
+		type Origin = Origin;
+		type BlockWeights = ();
+		type BlockLength = ();
+		type DbWeight = ();
+		type Call = Call;
+		type Index = u32;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = AccountId32;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = generic::Header<Self::BlockNumber, BlakeTwo256>;
+		type Event = Event;
+		type BlockHashCount = BlockHashCount;
+		type Version = ();
+		type PalletInfo = PalletInfo;
+		type AccountData = pallet_balances::AccountData<u64>;
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type SystemWeightInfo = ();
+		type SS58Prefix = SS58Prefix;
+		type OnSetCode = ();
+	}
+
+	impl pallet_balances::Config for Test {
+		type Balance = u64;
+		type Event = Event;
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type AccountStore = System;
+		type MaxLocks = ();
+		type WeightInfo = ();
+	}
+
+	impl account_linker::Config for Test {
+		type Event = Event;
+		type WeightInfo = ();
+		type BitcoinNetwork = BitcoinNetwork;
+		type MaxEthLinks = MaxEthLinks;
+		type MaxBtcLinks = MaxBtcLinks;
+		type MaxPolkadotLinks = MaxPolkadotLinks;
+		type UniqueLinks = UniqueLinks;
+		type Signature = MultiSignature;
+		type Signer = MultiSigner;
+		type Currency = Balances;
+		type EthLinkDeposit = Deposit;
+		type BtcLinkDeposit = Deposit;
+		type PolkadotLinkDeposit = Deposit;
+		type OnLinkChanged = ();
+		type UnsignedPriority = UnsignedPriority;
+		type UnsignedLinkInterval = UnsignedLinkInterval;
+		type LinkPolicy = Policy;
+		type LinkDelegates = ();
+		type LinkVerifiers = ();
+		type MaxChainLinks = MaxChainLinks;
+		type ChainLinkDeposit = Deposit;
+		type MaxPendingPerOrigin = MaxPendingPerOrigin;
+		type MaxPendingExpiriesPerBlock = MaxPendingExpiriesPerBlock;
+		type PolkadotRequestDeposit = Deposit;
+		type MaxUnsignedLinksPerBlock = MaxUnsignedLinksPerBlock;
+	}
+
+	pub fn new_test_ext() -> sp_io::TestExternalities {
+		BITCOIN_NETWORK.with(|v| *v.borrow_mut() = BTCNetwork::Mainnet);
+		set_unique_links(true);
+		set_link_deposit(0);
+		set_link_policy(account_linker::LinkPolicy::Anyone);
+		system::GenesisConfig::default()
+			.build_storage::<Test>()
+			.unwrap()
+			.into()
+	}
+}
@@ -1,15 +1,41 @@
-use crate::{mock::*};
+use crate::{mock::*, LinkSlot};
 
 use codec::Encode;
-use parity_crypto::Keccak256;
-use frame_support::{assert_ok, assert_noop};
-use sp_runtime::AccountId32;
+use crate::weights::WeightInfo;
+use frame_support::{assert_ok, assert_noop, storage::bounded_vec::BoundedVec, traits::{Currency, Get}, weights::GetDispatchInfo};
+use std::convert::TryFrom;
+use sp_runtime::{AccountId32, traits::ValidateUnsigned, transaction_validity::TransactionSource};
 
 use bitcoin::network::constants::Network;
 use bitcoin::util::address::Address;
 use bitcoin::util::key;
 use bitcoin::secp256k1::{Secp256k1, Message as BTCMessage};
 use bitcoin::secp256k1::rand::thread_rng;
+use bitcoin::hashes::{sha256d, Hash};
+
+fn generate_msg(account: &AccountId32, block_number: u32) -> BTCMessage {
+
+	let mut bytes = b"Link Litentry: ".encode();
+	let mut account_vec = account.encode();
+	let mut expiring_block_number_vec = block_number.encode();
+
+	let mut genesis_hash_vec = System::block_hash(0).encode();
+	let mut nonce_vec = AccountLinker::link_nonce(account).encode();
+
+	bytes.append(&mut account_vec);
+	bytes.append(&mut expiring_block_number_vec);
+	bytes.append(&mut genesis_hash_vec);
+	// index of the account linker pallet in the mock runtime
+	bytes.push(1);
+	bytes.append(&mut nonce_vec);
+
+	// Bitcoin signed message, the payload is shorter than 253 bytes so its varint length is one byte
+	let mut data = b"\x18Bitcoin Signed Message:\n".encode();
+	data.push(bytes.len() as u8);
+	data.append(&mut bytes);
+
+	BTCMessage::from_slice(&sha256d::Hash::hash(&data)[..]).unwrap()
+}
 
 #[test]
 fn test_invalid_expiring_block_number_btc() {
@@ -28,14 +54,7 @@ fn test_invalid_expiring_block_number_btc() {
 		let account: AccountId32 = AccountId32::from([255u8; 32]);
 		let block_number: u32 = crate::EXPIRING_BLOCK_NUMBER_MAX + 1;
 
-		let mut bytes = b"Link Litentry: ".encode();
-		let mut account_vec = account.encode();
-		let mut expiring_block_number_vec = block_number.encode();
-
-		bytes.append(&mut account_vec);
-		bytes.append(&mut expiring_block_number_vec);
-
-		let message = BTCMessage::from_slice(&bytes.keccak256()).unwrap();
+		let message = generate_msg(&account, block_number);
 
 		let (v, rs) = s.sign_recoverable(&message, &pair.0).serialize_compact();
 
@@ -54,7 +73,7 @@ fn test_invalid_expiring_block_number_btc() {
 				block_number,
 				r,
 				s,
-				v.to_i32() as u8),
+				31 + v.to_i32() as u8),
 			AccountLinkerError::InvalidExpiringBlockNumber
 		);
 
@@ -81,14 +100,7 @@ fn test_btc_link_p2pkh() {
 		let account: AccountId32 = AccountId32::from([255u8; 32]);
 		let block_number: u32 = 99999;
 
-		let mut bytes = b"Link Litentry: ".encode();
-		let mut account_vec = account.encode();
-		let mut expiring_block_number_vec = block_number.encode();
-
-		bytes.append(&mut account_vec);
-		bytes.append(&mut expiring_block_number_vec);
-
-		let message = BTCMessage::from_slice(&bytes.keccak256()).unwrap();
+		let message = generate_msg(&account, block_number);
 
 		let (v, rs) = s.sign_recoverable(&message, &pair.0).serialize_compact();
 
@@ -108,17 +120,18 @@ fn test_btc_link_p2pkh() {
 			block_number,
 			r,
 			s,
-			v.to_i32() as u8
+			31 + v.to_i32() as u8
 		));
 
 		let addr_stored = String::from_utf8(AccountLinker::btc_addresses(&account)[0].clone()).unwrap();
 
 		assert_eq!(addr_stored, address.to_string());
+		assert_eq!(AccountLinker::btc_owners(&addr_expected), vec![account.clone()]);
 
 		assert_eq!(
 			events(),
 			[
-				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr_expected)),
+				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr_expected, 0, None)),
 			]
 		);
 
@@ -146,14 +159,7 @@ fn test_btc_link_p2wpkh() {
 		let account: AccountId32 = AccountId32::from([255u8; 32]);
 		let block_number: u32 = 99999;
 
-		let mut bytes = b"Link Litentry: ".encode();
-		let mut account_vec = account.encode();
-		let mut expiring_block_number_vec = block_number.encode();
-
-		bytes.append(&mut account_vec);
-		bytes.append(&mut expiring_block_number_vec);
-
-		let message = BTCMessage::from_slice(&bytes.keccak256()).unwrap();
+		let message = generate_msg(&account, block_number);
 
 		let (v, rs) = s.sign_recoverable(&message, &pair.0).serialize_compact();
 
@@ -173,7 +179,7 @@ fn test_btc_link_p2wpkh() {
 			block_number,
 			r,
 			s,
-			v.to_i32() as u8
+			39 + v.to_i32() as u8
 		));
 
 		let addr_stored = String::from_utf8(AccountLinker::btc_addresses(&account)[0].clone()).unwrap();
@@ -183,8 +189,670 @@ fn test_btc_link_p2wpkh() {
         assert_eq!(
 			events(),
 			[
-				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr_expected)),
+				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr_expected, 0, None)),
 			]
         );
 	});
 }
+
+#[test]
+fn test_btc_link_p2shwpkh() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+		// Generate random key pair
+		let s = Secp256k1::new();
+		let pair = s.generate_keypair(&mut thread_rng());
+		let public_key = key::PublicKey {
+			compressed: true,
+			key: pair.1,
+		};
+
+		// Generate pay-to-witness-pubkey-hash nested in pay-to-script-hash address
+		let address = Address::p2shwpkh(&public_key, Network::Bitcoin).unwrap();
+
+		let account: AccountId32 = AccountId32::from([255u8; 32]);
+		let block_number: u32 = 99999;
+
+		let message = generate_msg(&account, block_number);
+
+		let (v, rs) = s.sign_recoverable(&message, &pair.0).serialize_compact();
+
+		let mut r = [0u8; 32];
+		let mut s = [0u8; 32];
+
+		r[..32].copy_from_slice(&rs[..32]);
+		s[..32].copy_from_slice(&rs[32..64]);
+
+        let addr_expected = address.clone().to_string().as_bytes().to_vec();
+		assert_eq!(addr_expected[0], b'3');
+
+		// Uncompressed key header can't be used for P2SH-P2WPKH
+		assert_noop!(
+			AccountLinker::link_btc(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				addr_expected.clone(),
+				block_number,
+				r,
+				s,
+				27 + v.to_i32() as u8),
+			AccountLinkerError::InvalidBTCSignatureHeader
+		);
+
+		assert_ok!(AccountLinker::link_btc(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			addr_expected.clone(),
+			block_number,
+			r,
+			s,
+			35 + v.to_i32() as u8
+		));
+
+		// The first link bumps the nonce, so the message is signed again
+		let message = generate_msg(&account, block_number);
+		let (v, rs) = Secp256k1::new().sign_recoverable(&message, &pair.0).serialize_compact();
+
+		r[..32].copy_from_slice(&rs[..32]);
+		s[..32].copy_from_slice(&rs[32..64]);
+
+		// An address is linked to an account in one slot only
+		assert_noop!(
+			AccountLinker::link_btc(
+				Origin::signed(account.clone()),
+				account.clone(),
+				1,
+				addr_expected.clone(),
+				block_number,
+				r,
+				s,
+				31 + v.to_i32() as u8),
+			AccountLinkerError::DuplicateLink
+		);
+
+		// Electrum style header of a compressed key is also accepted
+		assert_ok!(AccountLinker::link_btc(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			addr_expected.clone(),
+			block_number,
+			r,
+			s,
+			31 + v.to_i32() as u8
+		));
+
+		assert_eq!(AccountLinker::btc_addresses(&account), vec![addr_expected.clone()]);
+
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr_expected.clone(), 0, None)),
+				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr_expected.clone(), 0, Some(addr_expected))),
+			]
+		);
+	});
+}
+
+#[test]
+fn test_btc_link_p2pkh_uncompressed() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+		// Generate random key pair
+		let s = Secp256k1::new();
+		let pair = s.generate_keypair(&mut thread_rng());
+		let public_key = key::PublicKey {
+			compressed: false,
+			key: pair.1,
+		};
+
+		// Generate pay-to-pubkey-hash address of the uncompressed public key
+		let address = Address::p2pkh(&public_key, Network::Bitcoin);
+
+		let account: AccountId32 = AccountId32::from([255u8; 32]);
+		let block_number: u32 = 99999;
+
+		let message = generate_msg(&account, block_number);
+
+		let (v, rs) = s.sign_recoverable(&message, &pair.0).serialize_compact();
+
+		let mut r = [0u8; 32];
+		let mut s = [0u8; 32];
+
+		r[..32].copy_from_slice(&rs[..32]);
+		s[..32].copy_from_slice(&rs[32..64]);
+
+        let addr_expected = address.clone().to_string().as_bytes().to_vec();
+
+		// Header of a compressed key yields a different address
+		assert_noop!(
+			AccountLinker::link_btc(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				addr_expected.clone(),
+				block_number,
+				r,
+				s,
+				31 + v.to_i32() as u8),
+			AccountLinkerError::UnexpectedAddress
+		);
+
+		assert_ok!(AccountLinker::link_btc(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			addr_expected.clone(),
+			block_number,
+			r,
+			s,
+			27 + v.to_i32() as u8
+		));
+
+		assert_eq!(AccountLinker::btc_addresses(&account), vec![addr_expected]);
+	});
+}
+
+#[test]
+fn test_btc_link_invalid_signature_header() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+		// Generate random key pair
+		let s = Secp256k1::new();
+		let pair = s.generate_keypair(&mut thread_rng());
+		let public_key = key::PublicKey {
+			compressed: true,
+			key: pair.1,
+		};
+
+		let p2pkh = Address::p2pkh(&public_key, Network::Bitcoin).to_string().as_bytes().to_vec();
+		let p2wpkh = Address::p2wpkh(&public_key, Network::Bitcoin).unwrap().to_string().as_bytes().to_vec();
+
+		let account: AccountId32 = AccountId32::from([255u8; 32]);
+		let block_number: u32 = 99999;
+
+		let message = generate_msg(&account, block_number);
+
+		let (v, rs) = s.sign_recoverable(&message, &pair.0).serialize_compact();
+
+		let mut r = [0u8; 32];
+		let mut s = [0u8; 32];
+
+		r[..32].copy_from_slice(&rs[..32]);
+		s[..32].copy_from_slice(&rs[32..64]);
+
+		// Raw recovery id is not a BIP-137 header
+		assert_noop!(
+			AccountLinker::link_btc(Origin::signed(account.clone()), account.clone(), 0, p2pkh.clone(), block_number, r, s, v.to_i32() as u8),
+			AccountLinkerError::InvalidBTCSignatureHeader
+		);
+
+		// P2WPKH header for a P2PKH address
+		assert_noop!(
+			AccountLinker::link_btc(Origin::signed(account.clone()), account.clone(), 0, p2pkh, block_number, r, s, 39 + v.to_i32() as u8),
+			AccountLinkerError::InvalidBTCSignatureHeader
+		);
+
+		// Uncompressed key header for a P2WPKH address
+		assert_noop!(
+			AccountLinker::link_btc(Origin::signed(account.clone()), account.clone(), 0, p2wpkh, block_number, r, s, 27 + v.to_i32() as u8),
+			AccountLinkerError::InvalidBTCSignatureHeader
+		);
+	});
+}
+
+#[test]
+fn test_btc_unlink() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([255u8; 32]);
+		let addrs: Vec<Vec<u8>> = (0..3u8).map(|i| {
+			let s = Secp256k1::new();
+			let pair = s.generate_keypair(&mut thread_rng());
+			let public_key = key::PublicKey {
+				compressed: true,
+				key: pair.1,
+			};
+			if i % 2 == 0 {
+				Address::p2pkh(&public_key, Network::Bitcoin).to_string().as_bytes().to_vec()
+			} else {
+				Address::p2wpkh(&public_key, Network::Bitcoin).unwrap().to_string().as_bytes().to_vec()
+			}
+		}).collect();
+		crate::BitcoinLink::<Test>::insert(&account, BoundedVec::try_from(addrs.clone()).unwrap());
+
+		assert_ok!(AccountLinker::unlink_btc(Origin::signed(account.clone()), LinkSlot::Address(addrs[1].clone())));
+		assert_eq!(AccountLinker::btc_addresses(&account), vec![addrs[0].clone(), addrs[2].clone()]);
+
+		assert_ok!(AccountLinker::unlink_btc(Origin::signed(account.clone()), LinkSlot::Index(1)));
+		assert_eq!(AccountLinker::btc_addresses(&account), vec![addrs[0].clone()]);
+
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::BtcAddressUnlinked(account.clone(), addrs[1].clone())),
+				Event::account_linker(crate::Event::BtcAddressUnlinked(account.clone(), addrs[2].clone())),
+			]
+		);
+
+		assert_noop!(
+			AccountLinker::unlink_btc(Origin::signed(account.clone()), LinkSlot::Index(1)),
+			AccountLinkerError::LinkNotFound
+		);
+	});
+}
+
+#[test]
+fn test_btc_link_with_link() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let s = Secp256k1::new();
+		let pair = s.generate_keypair(&mut thread_rng());
+		let public_key = key::PublicKey {
+			compressed: true,
+			key: pair.1,
+		};
+		let addr = Address::p2wpkh(&public_key, Network::Bitcoin).unwrap().to_string().as_bytes().to_vec();
+
+		let account: AccountId32 = AccountId32::from([255u8; 32]);
+		let block_number: u32 = 99999;
+
+		let (v, rs) = s.sign_recoverable(&generate_msg(&account, block_number), &pair.0).serialize_compact();
+		let mut signature = rs.to_vec();
+		signature.push(39 + v.to_i32() as u8);
+
+		// `link` with the chain id of `BtcVerifier` links the address like `link_btc`
+		assert_ok!(AccountLinker::link(
+			Origin::signed(account.clone()),
+			BtcVerifier::<Test>::CHAIN_ID,
+			account.clone(),
+			0,
+			addr.clone(),
+			block_number,
+			signature
+		));
+		assert_eq!(AccountLinker::btc_addresses(&account), vec![addr.clone()]);
+		assert_eq!(AccountLinker::btc_owners(&addr), vec![account.clone()]);
+		assert!(AccountLinker::linked_accounts(&account).chains.is_empty());
+
+		assert_ok!(AccountLinker::unlink(
+			Origin::signed(account.clone()),
+			BtcVerifier::<Test>::CHAIN_ID,
+			LinkSlot::Index(0)
+		));
+		assert!(AccountLinker::btc_addresses(&account).is_empty());
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr.clone(), 0, None)),
+				Event::account_linker(crate::Event::BtcAddressUnlinked(account, addr)),
+			]
+		);
+	});
+}
+
+#[test]
+fn test_btc_link_p2tr() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+		// BIP-322 simple signatures of a key path spend, signed with the output key of the address
+		let addr_expected = b"bc1p26ej3vcv306c883yqkr50pu5pz7mxcjpmjwzulrpn74p9v5jp9nshfeja8".to_vec();
+		let account: AccountId32 = AccountId32::from([255u8; 32]);
+		let block_number: u32 = 99999;
+
+		// SIGHASH_DEFAULT, signed with link nonce 0
+		let r_default = [67, 94, 163, 147, 61, 163, 6, 244, 247, 118, 191, 186, 175, 119, 242, 3, 118, 229, 68, 159, 51, 68, 23, 237, 6, 185, 119, 130, 121, 248, 169, 211];
+		let s_default = [186, 6, 248, 216, 225, 88, 162, 157, 20, 88, 33, 170, 64, 126, 244, 29, 134, 92, 254, 246, 76, 229, 6, 181, 29, 48, 211, 237, 8, 231, 206, 125];
+		// SIGHASH_ALL, signed with link nonce 1
+		let r_all = [74, 8, 180, 177, 12, 228, 241, 37, 209, 68, 160, 105, 220, 222, 193, 163, 126, 44, 172, 5, 235, 11, 20, 176, 240, 103, 25, 2, 188, 120, 207, 236];
+		let s_all = [26, 250, 165, 202, 111, 154, 130, 131, 190, 8, 143, 17, 255, 206, 211, 122, 234, 254, 242, 158, 90, 142, 155, 62, 139, 51, 86, 194, 24, 0, 171, 60];
+
+		// Signature doesn't commit to this sighash type
+		assert_noop!(
+			AccountLinker::link_btc(Origin::signed(account.clone()), account.clone(), 0, addr_expected.clone(), block_number, r_default, s_default, 1),
+			AccountLinkerError::SchnorrVerifyFailure
+		);
+
+		// Signature doesn't commit to this expiring block number
+		assert_noop!(
+			AccountLinker::link_btc(Origin::signed(account.clone()), account.clone(), 0, addr_expected.clone(), block_number + 1, r_default, s_default, 0),
+			AccountLinkerError::SchnorrVerifyFailure
+		);
+
+		// Corrupted checksum
+		let mut addr_invalid = addr_expected.clone();
+		addr_invalid[61] = b'9';
+		assert_noop!(
+			AccountLinker::link_btc(Origin::signed(account.clone()), account.clone(), 0, addr_invalid, block_number, r_default, s_default, 0),
+			AccountLinkerError::InvalidBTCAddress
+		);
+
+		assert_ok!(AccountLinker::link_btc(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			addr_expected.clone(),
+			block_number,
+			r_default,
+			s_default,
+			0
+		));
+
+		// The address is relinked in its own slot
+		assert_ok!(AccountLinker::link_btc(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			addr_expected.clone(),
+			block_number,
+			r_all,
+			s_all,
+			1
+		));
+
+		assert_eq!(AccountLinker::btc_addresses(&account), vec![addr_expected.clone()]);
+		assert_eq!(AccountLinker::btc_owners(&addr_expected), vec![account.clone()]);
+
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr_expected.clone(), 0, None)),
+				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr_expected.clone(), 0, Some(addr_expected.clone()))),
+			]
+		);
+
+		assert_ok!(AccountLinker::unlink_btc(Origin::signed(account.clone()), LinkSlot::Index(0)));
+		assert!(AccountLinker::btc_owners(&addr_expected).is_empty());
+	});
+}
+
+#[test]
+fn test_btc_link_weight() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([255u8; 32]);
+		let weight = |addr: &[u8]| {
+			crate::Call::<Test>::link_btc(account.clone(), 0, addr.to_vec(), 99999, [0u8; 32], [0u8; 32], 0)
+				.get_dispatch_info()
+				.weight
+		};
+
+		// Taproot signatures are verified in the runtime rather than recovered by a host function
+		assert_eq!(
+			weight(b"bc1p26ej3vcv306c883yqkr50pu5pz7mxcjpmjwzulrpn74p9v5jp9nshfeja8"),
+			<() as WeightInfo>::link_btc_taproot(MaxBtcLinks::get())
+		);
+		assert_eq!(weight(b"1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2"), <() as WeightInfo>::link_btc(MaxBtcLinks::get()));
+		assert_eq!(weight(b"bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"), <() as WeightInfo>::link_btc(MaxBtcLinks::get()));
+		assert!(<() as WeightInfo>::link_btc_taproot(MaxBtcLinks::get()) > <() as WeightInfo>::link_btc(MaxBtcLinks::get()));
+	});
+}
+
+#[test]
+fn test_btc_link_testnet() {
+	new_test_ext_with_network(BTCNetwork::Testnet).execute_with(|| {
+
+        run_to_block(1);
+
+		// Generate random key pair
+		let secp = Secp256k1::new();
+		let pair = secp.generate_keypair(&mut thread_rng());
+		let public_key = key::PublicKey {
+			compressed: true,
+			key: pair.1,
+		};
+
+		let account: AccountId32 = AccountId32::from([255u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut r = [0u8; 32];
+		let mut s = [0u8; 32];
+		let mut v = 0;
+
+		let addresses = [
+			(Address::p2pkh(&public_key, Network::Testnet), 31),
+			(Address::p2shwpkh(&public_key, Network::Testnet).unwrap(), 35),
+			(Address::p2wpkh(&public_key, Network::Testnet).unwrap(), 39),
+		];
+
+		for (i, (address, header)) in addresses.iter().enumerate() {
+			let message = generate_msg(&account, block_number);
+			let (recovery_id, rs) = secp.sign_recoverable(&message, &pair.0).serialize_compact();
+
+			r[..32].copy_from_slice(&rs[..32]);
+			s[..32].copy_from_slice(&rs[32..64]);
+			v = recovery_id.to_i32() as u8;
+
+			assert_ok!(AccountLinker::link_btc(
+				Origin::signed(account.clone()),
+				account.clone(),
+				i as u32,
+				address.to_string().as_bytes().to_vec(),
+				block_number,
+				r,
+				s,
+				header + v
+			));
+
+			let addr_stored = String::from_utf8(AccountLinker::btc_addresses(&account)[i].clone()).unwrap();
+			assert_eq!(addr_stored, address.to_string());
+		}
+
+		// mainnet addresses are rejected by a testnet runtime
+		assert_noop!(
+			AccountLinker::link_btc(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				Address::p2pkh(&public_key, Network::Bitcoin).to_string().as_bytes().to_vec(),
+				block_number,
+				r,
+				s,
+				31 + v),
+			AccountLinkerError::InvalidBTCAddress
+		);
+		assert_noop!(
+			AccountLinker::link_btc(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				Address::p2wpkh(&public_key, Network::Bitcoin).unwrap().to_string().as_bytes().to_vec(),
+				block_number,
+				r,
+				s,
+				39 + v),
+			AccountLinkerError::InvalidBTCAddress
+		);
+	});
+}
+
+#[test]
+fn test_btc_link_regtest() {
+	new_test_ext_with_network(BTCNetwork::Regtest).execute_with(|| {
+
+        run_to_block(1);
+
+		// Generate random key pair
+		let s = Secp256k1::new();
+		let pair = s.generate_keypair(&mut thread_rng());
+		let public_key = key::PublicKey {
+			compressed: true,
+			key: pair.1,
+		};
+
+		let account: AccountId32 = AccountId32::from([255u8; 32]);
+		let block_number: u32 = 99999;
+
+		let message = generate_msg(&account, block_number);
+
+		let (v, rs) = s.sign_recoverable(&message, &pair.0).serialize_compact();
+
+		let mut r = [0u8; 32];
+		let mut s = [0u8; 32];
+
+		r[..32].copy_from_slice(&rs[..32]);
+		s[..32].copy_from_slice(&rs[32..64]);
+
+		let address = Address::p2wpkh(&public_key, Network::Regtest).unwrap();
+		let addr_expected = address.to_string().as_bytes().to_vec();
+		assert!(addr_expected.starts_with(b"bcrt1q"));
+
+		assert_ok!(AccountLinker::link_btc(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			addr_expected.clone(),
+			block_number,
+			r,
+			s,
+			39 + v.to_i32() as u8
+		));
+
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr_expected, 0, None)),
+			]
+		);
+
+		// testnet segwit addresses don't share the regtest hrp
+		assert_noop!(
+			AccountLinker::link_btc(
+				Origin::signed(account.clone()),
+				account.clone(),
+				1,
+				Address::p2wpkh(&public_key, Network::Testnet).unwrap().to_string().as_bytes().to_vec(),
+				block_number,
+				r,
+				s,
+				39 + v.to_i32() as u8),
+			AccountLinkerError::InvalidBTCAddress
+		);
+	});
+}
+
+#[test]
+fn test_btc_link_mainnet_rejects_testnet_address() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+		// Generate random key pair
+		let s = Secp256k1::new();
+		let pair = s.generate_keypair(&mut thread_rng());
+		let public_key = key::PublicKey {
+			compressed: true,
+			key: pair.1,
+		};
+
+		let account: AccountId32 = AccountId32::from([255u8; 32]);
+		let block_number: u32 = 99999;
+
+		let message = generate_msg(&account, block_number);
+
+		let (v, rs) = s.sign_recoverable(&message, &pair.0).serialize_compact();
+
+		let mut r = [0u8; 32];
+		let mut s = [0u8; 32];
+
+		r[..32].copy_from_slice(&rs[..32]);
+		s[..32].copy_from_slice(&rs[32..64]);
+
+		assert_noop!(
+			AccountLinker::link_btc(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				Address::p2shwpkh(&public_key, Network::Testnet).unwrap().to_string().as_bytes().to_vec(),
+				block_number,
+				r,
+				s,
+				35 + v.to_i32() as u8),
+			AccountLinkerError::InvalidBTCAddress
+		);
+		assert_noop!(
+			AccountLinker::link_btc(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				Address::p2wpkh(&public_key, Network::Regtest).unwrap().to_string().as_bytes().to_vec(),
+				block_number,
+				r,
+				s,
+				39 + v.to_i32() as u8),
+			AccountLinkerError::InvalidBTCAddress
+		);
+	});
+}
+
+#[test]
+fn test_btc_link_unsigned() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let s = Secp256k1::new();
+		let pair = s.generate_keypair(&mut thread_rng());
+		let public_key = key::PublicKey {
+			compressed: true,
+			key: pair.1,
+		};
+		let address = Address::p2pkh(&public_key, Network::Bitcoin);
+
+		let account: AccountId32 = AccountId32::from([254u8; 32]);
+		let block_number: u32 = 99999;
+		let _ = Balances::deposit_creating(&account, ExistentialDeposit::get());
+		events();
+
+		let message = generate_msg(&account, block_number);
+
+		let (v, rs) = s.sign_recoverable(&message, &pair.0).serialize_compact();
+
+		let mut r = [0u8; 32];
+		let mut s = [0u8; 32];
+
+		r[..32].copy_from_slice(&rs[..32]);
+		s[..32].copy_from_slice(&rs[32..64]);
+
+		let addr_expected = address.to_string().as_bytes().to_vec();
+		let v = 31 + v.to_i32() as u8;
+
+		let call = crate::Call::link_btc_unsigned(account.clone(), 0, addr_expected.clone(), block_number, r, s, v, None);
+		assert_ok!(AccountLinker::validate_unsigned(TransactionSource::External, &call));
+
+		assert_ok!(AccountLinker::link_btc_unsigned(
+			Origin::none(),
+			account.clone(),
+			0,
+			addr_expected.clone(),
+			block_number,
+			r,
+			s,
+			v,
+			None
+		));
+
+		assert_eq!(AccountLinker::btc_addresses(&account), vec![addr_expected.clone()]);
+		assert_eq!(AccountLinker::last_unsigned_link(&account), Some(1));
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::BtcAddressLinked(account.clone(), addr_expected, 0, None)),
+			]
+		);
+	});
+}
@@ -0,0 +1,224 @@
+use crate::{mock::*, util_cosmos::adr036_sign_doc_hash, LinkSlot};
+use super::eth::negate_s;
+
+use frame_support::{assert_ok, assert_noop, traits::Currency};
+use sp_runtime::AccountId32;
+
+use bitcoin::bech32::{self, ToBase32};
+use bitcoin::hashes::{hash160, Hash};
+use bitcoin::secp256k1::{Secp256k1, Message, SecretKey, PublicKey};
+
+// The bech32 address of a key with the hrp `hrp`
+fn cosmos_addr(hrp: &str, secret: &SecretKey) -> Vec<u8> {
+	let pk = PublicKey::from_secret_key(&Secp256k1::new(), secret);
+	let hash = hash160::Hash::hash(&pk.serialize());
+	bech32::encode(hrp, hash.to_base32()).unwrap().into_bytes()
+}
+
+// The ADR-036 signature of the link message by `addr`
+fn sign_link_message(secret: &SecretKey, addr: &[u8], account: &AccountId32, block_number: u32) -> Vec<u8> {
+	let hash = adr036_sign_doc_hash(addr, &AccountLinker::link_message(account, block_number));
+	Secp256k1::new().sign(&Message::from_slice(&hash).unwrap(), secret).serialize_compact().to_vec()
+}
+
+#[test]
+fn test_link_cosmos() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([1u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = CosmosVerifier::<Test, CosmosHrp>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[1u8; 32]).unwrap();
+		let addr = cosmos_addr("cosmos", &secret);
+		let sig = sign_link_message(&secret, &addr, &account, block_number);
+
+		let _ = Balances::deposit_creating(&account, 100);
+		events();
+
+		assert_ok!(AccountLinker::link(
+			Origin::signed(account.clone()),
+			chain_id,
+			account.clone(),
+			0,
+			addr.clone(),
+			block_number,
+			sig.clone()
+		));
+
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![addr.clone()]);
+		assert_eq!(AccountLinker::chain_owners(chain_id, &addr), vec![account.clone()]);
+		assert_eq!(AccountLinker::link_nonce(&account), 1);
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(
+			events(),
+			[
+				Event::pallet_balances(pallet_balances::Event::Reserved(account.clone(), 10)),
+				Event::account_linker(crate::Event::AddressLinked(account.clone(), chain_id, addr.clone(), 0, None)),
+			]
+		);
+
+		// The signature commits to the nonce, so it can't be replayed
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 1, addr, block_number, sig),
+			AccountLinkerError::InvalidCosmosSignature
+		);
+	});
+}
+
+#[test]
+fn test_link_cosmos_invalid_signature() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([2u8; 32]);
+		let other: AccountId32 = AccountId32::from([3u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = CosmosVerifier::<Test, CosmosHrp>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[2u8; 32]).unwrap();
+		let addr = cosmos_addr("cosmos", &secret);
+
+		// signed for another account
+		let sig = sign_link_message(&secret, &addr, &other, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr.clone(), block_number, sig),
+			AccountLinkerError::InvalidCosmosSignature
+		);
+
+		// signed by another key
+		let secret = SecretKey::from_slice(&[3u8; 32]).unwrap();
+		let sig = sign_link_message(&secret, &addr, &account, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 0, addr, block_number, sig),
+			AccountLinkerError::InvalidCosmosSignature
+		);
+	});
+}
+
+#[test]
+fn test_link_cosmos_expired() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([4u8; 32]);
+		let chain_id = CosmosVerifier::<Test, CosmosHrp>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[4u8; 32]).unwrap();
+		let addr = cosmos_addr("cosmos", &secret);
+		let sig = sign_link_message(&secret, &addr, &account, 1);
+
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 0, addr, 1, sig),
+			AccountLinkerError::LinkRequestExpired
+		);
+	});
+}
+
+#[test]
+fn test_unlink_cosmos() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([5u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = CosmosVerifier::<Test, CosmosHrp>::CHAIN_ID;
+
+		let _ = Balances::deposit_creating(&account, 100);
+
+		let mut linked = Vec::new();
+		for i in 0..2u8 {
+			let secret = SecretKey::from_slice(&[10 + i; 32]).unwrap();
+			let addr = cosmos_addr("cosmos", &secret);
+			let sig = sign_link_message(&secret, &addr, &account, block_number);
+			assert_ok!(AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), i as u32, addr.clone(), block_number, sig));
+			linked.push(addr);
+		}
+		assert_eq!(Balances::reserved_balance(&account), 20);
+		events();
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Address(linked[0].clone())));
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![linked[1].clone()]);
+		assert!(AccountLinker::chain_owners(chain_id, &linked[0]).is_empty());
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(
+			events(),
+			[
+				Event::pallet_balances(pallet_balances::Event::Unreserved(account.clone(), 10)),
+				Event::account_linker(crate::Event::AddressUnlinked(account.clone(), chain_id, linked[0].clone())),
+			]
+		);
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Index(0)));
+		assert!(AccountLinker::chain_addresses(chain_id, &account).is_empty());
+		assert_eq!(Balances::reserved_balance(&account), 0);
+		assert_eq!(Balances::free_balance(&account), 100);
+
+		assert_noop!(
+			AccountLinker::unlink(Origin::signed(account), chain_id, LinkSlot::Index(0)),
+			AccountLinkerError::LinkNotFound
+		);
+	});
+}
+
+#[test]
+fn test_link_cosmos_high_s() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([2u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = CosmosVerifier::<Test, CosmosHrp>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[2u8; 32]).unwrap();
+		let addr = cosmos_addr("cosmos", &secret);
+
+		// the high-s form of a valid signature is rejected
+		let mut sig = sign_link_message(&secret, &addr, &account, block_number);
+		let mut s = [0u8; 32];
+		s.copy_from_slice(&sig[32..]);
+		sig[32..].copy_from_slice(&negate_s(&s));
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr, block_number, sig),
+			AccountLinkerError::InvalidCosmosSignature
+		);
+	});
+}
+
+#[test]
+fn test_link_cosmos_invalid_address() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([4u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = CosmosVerifier::<Test, CosmosHrp>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[4u8; 32]).unwrap();
+		let addr = cosmos_addr("cosmos", &secret);
+
+		let invalid_addrs = vec![
+			// another hrp
+			cosmos_addr("osmo", &secret),
+			// uppercase
+			addr.to_ascii_uppercase(),
+			// invalid checksum
+			b"cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xv".to_vec(),
+			// a segwit program of 32 bytes
+			b"cosmos1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0sxaggsw".to_vec(),
+		];
+
+		for invalid_addr in invalid_addrs {
+			let sig = sign_link_message(&secret, &invalid_addr, &account, block_number);
+			assert_noop!(
+				AccountLinker::link(
+					Origin::signed(account.clone()), chain_id, account.clone(), 0, invalid_addr, block_number, sig
+				),
+				AccountLinkerError::InvalidCosmosAddress
+			);
+		}
+	});
+}
@@ -1,37 +1,113 @@
-use crate::{mock::*};
+use crate::{mock::*, ExternalAddress, LinkChain, LinkSlot, LinkedAddresses};
 
 use codec::Encode;
 use parity_crypto::Keccak256;
 use parity_crypto::publickey::{Random, Generator, Message, sign, KeyPair};
-use frame_support::{assert_ok, assert_noop};
-use sp_runtime::AccountId32;
+use frame_support::{assert_ok, assert_noop, traits::{Currency, Get, UnfilteredDispatchable}};
+use sp_core::{sr25519, Pair, H256};
+use sp_runtime::{
+	AccountId32, MultiSignature,
+	traits::{BadOrigin, ValidateUnsigned},
+	transaction_validity::{InvalidTransaction, TransactionSource},
+};
 
 fn generate_msg(account: &AccountId32, block_number: u32) -> Message {
+	generate_msg_for_chain(account, block_number, System::block_hash(0))
+}
+
+fn generate_msg_for_chain(account: &AccountId32, block_number: u32, genesis_hash: H256) -> Message {
 
-	let mut bytes = b"\x19Ethereum Signed Message:\n51Link Litentry: ".encode();
+	let mut bytes = b"\x19Ethereum Signed Message:\n88Link Litentry: ".encode();
 	let mut account_vec = account.encode();
 	let mut expiring_block_number_vec = block_number.encode();
+	let mut genesis_hash_vec = genesis_hash.encode();
+	let mut nonce_vec = AccountLinker::link_nonce(account).encode();
 
 	bytes.append(&mut account_vec);
 	bytes.append(&mut expiring_block_number_vec);
+	bytes.append(&mut genesis_hash_vec);
+	// index of the account linker pallet in the mock runtime
+	bytes.push(1);
+	bytes.append(&mut nonce_vec);
 
 	Message::from(bytes.keccak256())
 }
 
+// EIP-712 hash of `LinkLitentry { account, expiringBlock, nonce }`
+fn generate_typed_msg(account: &AccountId32, block_number: u32) -> Message {
+	let uint256 = |n: u32| {
+		let mut res = [0u8; 32];
+		res[28..].copy_from_slice(&n.to_be_bytes());
+		res
+	};
+
+	let mut salt = System::block_hash(0).encode();
+	// index of the account linker pallet in the mock runtime
+	salt.push(1);
+
+	let mut domain = b"EIP712Domain(string name,string version,bytes32 salt)".keccak256().to_vec();
+	domain.extend_from_slice(&b"Litentry".keccak256());
+	domain.extend_from_slice(&b"1".keccak256());
+	domain.extend_from_slice(&salt.keccak256());
+
+	let mut link = b"LinkLitentry(bytes account,uint256 expiringBlock,uint256 nonce)".keccak256().to_vec();
+	link.extend_from_slice(&account.encode().keccak256());
+	link.extend_from_slice(&uint256(block_number));
+	link.extend_from_slice(&uint256(AccountLinker::link_nonce(account)));
+
+	let mut data = b"\x19\x01".to_vec();
+	data.extend_from_slice(&domain.keccak256());
+	data.extend_from_slice(&link.keccak256());
+
+	Message::from(data.keccak256())
+}
+
 fn generate_sig(key_pair: &KeyPair, msg: &Message) -> [u8; 65] {
 	sign(key_pair.secret(), &msg).unwrap().into_electrum()
 }
 
-fn generate_rsv(sig: &[u8; 65]) -> ([u8; 32], [u8; 32], u8) {
+fn generate_rsv(sig: &[u8; 65]) -> ([u8; 32], [u8; 32], u64) {
 	let mut r = [0u8; 32];
 	let mut s = [0u8; 32];
 
 	r[..32].copy_from_slice(&sig[..32]);
 	s[..32].copy_from_slice(&sig[32..64]);
-	let v = sig[64];
+	let v = sig[64] as u64;
 	(r, s, v)
 }
 
+// The unsigned link of a new eth address to `account` at slot `index`, authorised by the signature
+// of `signer` over the unsigned link message if any
+fn unsigned_link_call(account: &AccountId32, index: u32, block_number: u32, signer: Option<&sr25519::Pair>) -> crate::Call<Test> {
+	let key_pair = Random{}.generate();
+	let msg = generate_msg(account, block_number);
+	let sig = generate_sig(&key_pair, &msg);
+	let (r, s, v) = generate_rsv(&sig);
+	let addr = key_pair.address().to_fixed_bytes();
+
+	let account_signature = signer.map(|signer| MultiSignature::from(
+		signer.sign(&AccountLinker::unsigned_link_message(account, index, &addr, block_number))
+	));
+	crate::Call::link_eth_unsigned(account.clone(), index, addr, block_number, r, s, v, account_signature)
+}
+
+// `n - s` where `n` is the order of secp256k1, the other `s` of a valid signature
+pub(super) fn negate_s(s: &[u8; 32]) -> [u8; 32] {
+	let order = hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141").unwrap();
+	let mut res = [0u8; 32];
+	let mut borrow = 0i16;
+	for i in (0..32).rev() {
+		let mut diff = order[i] as i16 - s[i] as i16 - borrow;
+		borrow = 0;
+		if diff < 0 {
+			diff += 256;
+			borrow = 1;
+		}
+		res[i] = diff as u8;
+	}
+	res
+}
+
 #[test]
 fn test_expired_block_number_eth() {
 	new_test_ext().execute_with(|| {
@@ -128,10 +204,11 @@ fn test_insert_eth_address() {
 		let account: AccountId32 = AccountId32::from([5u8; 32]);
 		let block_number: u32 = 99999;
 
+		let max_links = MaxEthLinks::get() as usize;
 		let mut gen = Random{};
 		let mut expected_vec = Vec::new();
 
-		for i in 0..(MAX_ETH_LINKS) {
+		for i in 0..(max_links) {
 
 			let key_pair = gen.generate();
 
@@ -156,7 +233,7 @@ fn test_insert_eth_address() {
 			assert_eq!(
 					events(),
 					[
-						Event::account_linker(crate::Event::EthAddressLinked(account.clone(), key_pair.address().to_fixed_bytes().to_vec())),
+						Event::account_linker(crate::Event::EthAddressLinked(account.clone(), key_pair.address().to_fixed_bytes().to_vec(), i as u32, None)),
 					]
 				);
 		}
@@ -171,8 +248,9 @@ fn test_update_eth_address() {
 		let account: AccountId32 = AccountId32::from([40u8; 32]);
 		let block_number: u32 = 99999;
 
+		let max_links = MaxEthLinks::get() as usize;
 		let mut gen = Random{};
-		for i in 0..(MAX_ETH_LINKS) {
+		for i in 0..(max_links) {
 			let key_pair = gen.generate();
 			let msg = generate_msg(&account, block_number + i as u32);
 			let sig = generate_sig(&key_pair, &msg);
@@ -193,6 +271,7 @@ fn test_update_eth_address() {
 		let index: u32 = 2 as u32;
 		// Retrieve previous addr
 		let addr_before_update =  AccountLinker::eth_addresses(&account)[index as usize];
+		events();
 		// Update addr at slot `index`
 		let key_pair = gen.generate();
 		let block_number = block_number + 9 as u32;
@@ -214,6 +293,17 @@ fn test_update_eth_address() {
 		let updated_addr =  AccountLinker::eth_addresses(&account)[index as usize];
 		assert_ne!(updated_addr, addr_before_update);
 		assert_eq!(updated_addr, key_pair.address().to_fixed_bytes());
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::EthAddressLinked(
+					account.clone(),
+					updated_addr.to_vec(),
+					index,
+					Some(addr_before_update.to_vec()),
+				)),
+			]
+		);
 	});
 }
 
@@ -225,10 +315,11 @@ fn test_eth_address_pool_overflow() {
 		let account: AccountId32 = AccountId32::from([113u8; 32]);
 		let block_number: u32 = 99999;
 
+		let max_links = MaxEthLinks::get() as usize;
 		let mut gen = Random{};
 		let mut expected_vec = Vec::new();
 
-		for index in 0..(MAX_ETH_LINKS*2) {
+		for index in 0..(max_links*2) {
 			let key_pair = gen.generate();
 
 			let msg = generate_msg(&account, block_number);
@@ -246,13 +337,975 @@ fn test_eth_address_pool_overflow() {
 				v
 			));
 
-			if index < MAX_ETH_LINKS {
+			if index < max_links {
 				expected_vec.push(key_pair.address().to_fixed_bytes());
 			} else {
-				expected_vec[MAX_ETH_LINKS-1] = key_pair.address().to_fixed_bytes();
+				expected_vec[max_links-1] = key_pair.address().to_fixed_bytes();
 			}
 		}
-		assert_eq!(AccountLinker::eth_addresses(&account).len(), MAX_ETH_LINKS);
+		assert_eq!(AccountLinker::eth_addresses(&account).len(), max_links);
 		assert_eq!(AccountLinker::eth_addresses(&account), expected_vec);
 	});
 }
+
+#[test]
+fn test_unlink_eth_address() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([7u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut gen = Random{};
+		let mut linked = Vec::new();
+
+		for i in 0..(MaxEthLinks::get() as usize) {
+			let key_pair = gen.generate();
+			let msg = generate_msg(&account, block_number);
+			let sig = generate_sig(&key_pair, &msg);
+			let (r, s, v) = generate_rsv(&sig);
+
+			assert_ok!(AccountLinker::link_eth(
+				Origin::signed(account.clone()),
+				account.clone(),
+				i as u32,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v
+			));
+			linked.push(key_pair.address().to_fixed_bytes());
+		}
+		events();
+
+		// Unlink by index, later slots are shifted down
+		assert_ok!(AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Index(0)));
+		assert_eq!(AccountLinker::eth_addresses(&account), vec![linked[1], linked[2]]);
+
+		// Unlink by address
+		assert_ok!(AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Address(linked[2])));
+		assert_eq!(AccountLinker::eth_addresses(&account), vec![linked[1]]);
+
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::EthAddressUnlinked(account.clone(), linked[0].to_vec())),
+				Event::account_linker(crate::Event::EthAddressUnlinked(account.clone(), linked[2].to_vec())),
+			]
+		);
+
+		assert_noop!(
+			AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Index(1)),
+			AccountLinkerError::LinkNotFound
+		);
+		assert_noop!(
+			AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Address(linked[0])),
+			AccountLinkerError::LinkNotFound
+		);
+
+		assert_ok!(AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Index(0)));
+		assert!(!crate::EthereumLink::<Test>::contains_key(&account));
+	});
+}
+
+#[test]
+fn test_link_eth_replay() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([9u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut gen = Random{};
+		let key_pair = gen.generate();
+
+		// A message signed for another chain is rejected
+		let msg = generate_msg_for_chain(&account, block_number, H256::repeat_byte(1));
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_noop!(
+			AccountLinker::link_eth(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v),
+			AccountLinkerError::UnexpectedAddress
+		);
+
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_eq!(AccountLinker::link_nonce(&account), 0);
+		assert_ok!(AccountLinker::link_eth(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			key_pair.address().to_fixed_bytes(),
+			block_number,
+			r,
+			s,
+			v
+		));
+		assert_eq!(AccountLinker::link_nonce(&account), 1);
+
+		// The signature can't be replayed after the address is unlinked
+		assert_ok!(AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Index(0)));
+		assert_noop!(
+			AccountLinker::link_eth(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v),
+			AccountLinkerError::UnexpectedAddress
+		);
+		assert!(AccountLinker::eth_addresses(&account).is_empty());
+	});
+}
+
+#[test]
+fn test_eth_owner() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([10u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut gen = Random{};
+		let mut linked = Vec::new();
+
+		for i in 0..2 {
+			let key_pair = gen.generate();
+			let msg = generate_msg(&account, block_number);
+			let sig = generate_sig(&key_pair, &msg);
+			let (r, s, v) = generate_rsv(&sig);
+
+			assert_ok!(AccountLinker::link_eth(
+				Origin::signed(account.clone()),
+				account.clone(),
+				i,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v
+			));
+			linked.push(key_pair.address().to_fixed_bytes());
+		}
+
+		assert_eq!(AccountLinker::eth_owners(&linked[0]), vec![account.clone()]);
+		assert_eq!(AccountLinker::eth_owners(&linked[1]), vec![account.clone()]);
+
+		// Replacing a slot drops the owner of the replaced address
+		let key_pair = gen.generate();
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_ok!(AccountLinker::link_eth(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			key_pair.address().to_fixed_bytes(),
+			block_number,
+			r,
+			s,
+			v
+		));
+
+		assert!(AccountLinker::eth_owners(&linked[0]).is_empty());
+		assert_eq!(AccountLinker::eth_owners(&key_pair.address().to_fixed_bytes()), vec![account.clone()]);
+
+		assert_ok!(AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Address(linked[1])));
+		assert!(AccountLinker::eth_owners(&linked[1]).is_empty());
+	});
+}
+
+#[test]
+fn test_eth_address_already_linked() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([11u8; 32]);
+		let other: AccountId32 = AccountId32::from([12u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut gen = Random{};
+		let key_pair = gen.generate();
+
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_ok!(AccountLinker::link_eth(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			key_pair.address().to_fixed_bytes(),
+			block_number,
+			r,
+			s,
+			v
+		));
+
+		let msg = generate_msg(&other, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_noop!(
+			AccountLinker::link_eth(
+				Origin::signed(other.clone()),
+				other.clone(),
+				0,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v),
+			AccountLinkerError::AddressAlreadyLinked
+		);
+
+		// The same address can be linked to several accounts if links aren't unique
+		set_unique_links(false);
+
+		assert_ok!(AccountLinker::link_eth(
+			Origin::signed(other.clone()),
+			other.clone(),
+			0,
+			key_pair.address().to_fixed_bytes(),
+			block_number,
+			r,
+			s,
+			v
+		));
+
+		let mut owners = AccountLinker::eth_owners(&key_pair.address().to_fixed_bytes());
+		owners.sort();
+		assert_eq!(owners, vec![account, other]);
+	});
+}
+
+#[test]
+fn test_link_eth_with_link() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([5u8; 32]);
+		let block_number: u32 = 99999;
+
+		let key_pair = Random{}.generate();
+		let addr = key_pair.address().to_fixed_bytes();
+		let sig = generate_sig(&key_pair, &generate_msg(&account, block_number));
+
+		// `link` with the chain id of `EthVerifier` links the address like `link_eth`
+		assert_ok!(AccountLinker::link(
+			Origin::signed(account.clone()),
+			EthVerifier::<Test>::CHAIN_ID,
+			account.clone(),
+			0,
+			addr.to_vec(),
+			block_number,
+			sig.to_vec()
+		));
+		assert_eq!(AccountLinker::eth_addresses(&account), vec![addr]);
+		assert_eq!(AccountLinker::eth_owners(&addr), vec![account.clone()]);
+		assert!(AccountLinker::linked_accounts(&account).chains.is_empty());
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::EthAddressLinked(account.clone(), addr.to_vec(), 0, None)),
+			]
+		);
+
+		assert_ok!(AccountLinker::unlink(
+			Origin::signed(account.clone()),
+			EthVerifier::<Test>::CHAIN_ID,
+			LinkSlot::Address(addr.to_vec())
+		));
+		assert!(AccountLinker::eth_addresses(&account).is_empty());
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::EthAddressUnlinked(account.clone(), addr.to_vec())),
+			]
+		);
+
+		// eth addresses are 20 bytes
+		let key_pair = Random{}.generate();
+		let sig = generate_sig(&key_pair, &generate_msg(&account, block_number));
+		assert_noop!(
+			AccountLinker::link(
+				Origin::signed(account.clone()),
+				EthVerifier::<Test>::CHAIN_ID,
+				account,
+				0,
+				hex::encode(key_pair.address()).into_bytes(),
+				block_number,
+				sig.to_vec()
+			),
+			AccountLinkerError::InvalidEthAddress
+		);
+	});
+}
+
+#[test]
+fn test_link_eth_typed() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([13u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut gen = Random{};
+		let key_pair = gen.generate();
+
+		// A personal_sign signature isn't valid typed data
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_noop!(
+			AccountLinker::link_eth_typed(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v),
+			AccountLinkerError::UnexpectedAddress
+		);
+
+		let msg = generate_typed_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_ok!(AccountLinker::link_eth_typed(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			key_pair.address().to_fixed_bytes(),
+			block_number,
+			r,
+			s,
+			v
+		));
+
+		assert_eq!(AccountLinker::eth_addresses(&account), vec![key_pair.address().to_fixed_bytes()]);
+		assert_eq!(AccountLinker::link_nonce(&account), 1);
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::EthAddressLinked(account.clone(), key_pair.address().to_fixed_bytes().to_vec(), 0, None)),
+			]
+		);
+
+		// The signature commits to the nonce, so it can't be replayed
+		assert_noop!(
+			AccountLinker::link_eth_typed(
+				Origin::signed(account.clone()),
+				account.clone(),
+				1,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v),
+			AccountLinkerError::UnexpectedAddress
+		);
+	});
+}
+
+#[test]
+fn test_link_eth_block_number_u64() {
+	use crate::mock::block_number_u64::{self, Origin, System, AccountLinker};
+
+	block_number_u64::new_test_ext().execute_with(|| {
+
+		System::set_block_number(1);
+
+		let account: AccountId32 = AccountId32::from([14u8; 32]);
+		let block_number: u64 = 99999;
+
+		let mut gen = Random{};
+		let key_pair = gen.generate();
+
+		// The message is 92 bytes long with an 8-byte block number
+		let link_msg = AccountLinker::link_message(&account, block_number);
+		assert_eq!(link_msg.len(), 92);
+
+		let mut bytes = b"\x19Ethereum Signed Message:\n92".encode();
+		bytes.extend_from_slice(&link_msg);
+		let msg = Message::from(bytes.keccak256());
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_ok!(AccountLinker::link_eth(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			key_pair.address().to_fixed_bytes(),
+			block_number,
+			r,
+			s,
+			v
+		));
+
+		assert_eq!(AccountLinker::eth_addresses(&account), vec![key_pair.address().to_fixed_bytes()]);
+	});
+}
+
+#[test]
+fn test_eth_link_deposit() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([15u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut gen = Random{};
+		let key_pair = gen.generate();
+
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_noop!(
+			AccountLinker::link_eth(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+
+		let _ = Balances::deposit_creating(&account, 100);
+
+		assert_ok!(AccountLinker::link_eth(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			key_pair.address().to_fixed_bytes(),
+			block_number,
+			r,
+			s,
+			v
+		));
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(AccountLinker::link_deposit(&account), 10);
+
+		// replacing a linked address doesn't reserve another deposit
+		let key_pair = gen.generate();
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_ok!(AccountLinker::link_eth(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			key_pair.address().to_fixed_bytes(),
+			block_number,
+			r,
+			s,
+			v
+		));
+		assert_eq!(Balances::reserved_balance(&account), 10);
+
+		assert_ok!(AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Index(0)));
+		assert_eq!(Balances::reserved_balance(&account), 0);
+		assert_eq!(AccountLinker::link_deposit(&account), 0);
+	});
+}
+
+#[test]
+fn test_eth_slot_deposits() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([16u8; 32]);
+		let block_number: u32 = 99999;
+		let _ = Balances::deposit_creating(&account, 100);
+
+		let mut gen = Random{};
+		let mut link = |index: u32| {
+			let key_pair = gen.generate();
+			let msg = generate_msg(&account, block_number);
+			let sig = generate_sig(&key_pair, &msg);
+			let (r, s, v) = generate_rsv(&sig);
+
+			assert_ok!(AccountLinker::link_eth(
+				Origin::signed(account.clone()),
+				account.clone(),
+				index,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v
+			));
+		};
+
+		set_link_deposit(10);
+		link(0);
+		set_link_deposit(20);
+		link(1);
+		// replacing a linked address keeps the deposit of its slot
+		link(0);
+		assert_eq!(AccountLinker::slot_deposits(&account, LinkChain::Eth), vec![10, 20]);
+		assert_eq!(Balances::reserved_balance(&account), 30);
+
+		// unlinking returns exactly the deposit of the slot, whatever the current deposit
+		set_link_deposit(5);
+		assert_ok!(AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Index(0)));
+		assert_eq!(Balances::reserved_balance(&account), 20);
+		assert_eq!(AccountLinker::slot_deposits(&account, LinkChain::Eth), vec![20]);
+
+		assert_ok!(AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Index(0)));
+		assert_eq!(Balances::reserved_balance(&account), 0);
+		assert_eq!(AccountLinker::link_deposit(&account), 0);
+		assert!(AccountLinker::slot_deposits(&account, LinkChain::Eth).is_empty());
+	});
+}
+
+#[test]
+fn test_linked_accounts() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([16u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut gen = Random{};
+		let key_pair = gen.generate();
+		let addr = key_pair.address().to_fixed_bytes();
+
+		assert_eq!(AccountLinker::linked_accounts(&account), LinkedAddresses::default());
+		assert!(AccountLinker::owner_of(&ExternalAddress::Eth(addr)).is_empty());
+
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_ok!(AccountLinker::link_eth(
+			Origin::signed(account.clone()),
+			account.clone(),
+			0,
+			addr,
+			block_number,
+			r,
+			s,
+			v
+		));
+
+		assert_eq!(
+			AccountLinker::linked_accounts(&account),
+			LinkedAddresses { eth: vec![addr], ..Default::default() }
+		);
+		assert_eq!(AccountLinker::owner_of(&ExternalAddress::Eth(addr)), vec![account.clone()]);
+		assert!(AccountLinker::owner_of(&ExternalAddress::Polkadot(account)).is_empty());
+	});
+}
+
+#[test]
+fn test_link_eth_signature_formats() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([16u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut gen = Random{};
+		let mut expected_vec = Vec::new();
+
+		// The raw recovery id and EIP-155 values of mainnet are accepted as well as 27/28
+		for (i, offset) in [0u64, 37].iter().enumerate() {
+			let key_pair = gen.generate();
+			let msg = generate_msg(&account, block_number);
+			let sig = generate_sig(&key_pair, &msg);
+			let (r, s, v) = generate_rsv(&sig);
+
+			assert_ok!(AccountLinker::link_eth(
+				Origin::signed(account.clone()),
+				account.clone(),
+				i as u32,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v - 27 + offset
+			));
+			expected_vec.push(key_pair.address().to_fixed_bytes());
+		}
+
+		// EIP-2098 compact signature
+		let key_pair = gen.generate();
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+		let mut vs = s;
+		vs[0] |= ((v - 27) as u8) << 7;
+
+		assert_ok!(AccountLinker::link_eth_compact(
+			Origin::signed(account.clone()),
+			account.clone(),
+			2,
+			key_pair.address().to_fixed_bytes(),
+			block_number,
+			r,
+			vs
+		));
+		expected_vec.push(key_pair.address().to_fixed_bytes());
+
+		assert_eq!(AccountLinker::eth_addresses(&account), expected_vec);
+
+		let key_pair = gen.generate();
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		assert_noop!(
+			AccountLinker::link_eth(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				29),
+			AccountLinkerError::EcdsaRecoverFailure
+		);
+
+		// The high-s twin of a valid signature is rejected
+		assert_noop!(
+			AccountLinker::link_eth(
+				Origin::signed(account.clone()),
+				account.clone(),
+				0,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				negate_s(&s),
+				v ^ 1),
+			AccountLinkerError::MalleableEthSignature
+		);
+	});
+}
+
+#[test]
+fn test_link_eth_eip155_chain_ids() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([17u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut gen = Random{};
+
+		// mainnet (37/38) and chain id 137, whose `v` doesn't fit in a byte
+		for (i, chain_id) in [1u64, 137].iter().enumerate() {
+			let key_pair = gen.generate();
+			let msg = generate_msg(&account, block_number);
+			let sig = generate_sig(&key_pair, &msg);
+			let (r, s, v) = generate_rsv(&sig);
+
+			assert_ok!(AccountLinker::link_eth(
+				Origin::signed(account.clone()),
+				account.clone(),
+				i as u32,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				chain_id * 2 + 35 + (v - 27)
+			));
+			assert_eq!(AccountLinker::eth_addresses(&account)[i], key_pair.address().to_fixed_bytes());
+		}
+	});
+}
+
+#[test]
+fn test_link_eth_unsigned() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([17u8; 32]);
+		let block_number: u32 = 99;
+		let _ = Balances::deposit_creating(&account, ExistentialDeposit::get());
+
+		let mut gen = Random{};
+		let key_pair = gen.generate();
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+		let addr = key_pair.address().to_fixed_bytes();
+
+		let call = crate::Call::link_eth_unsigned(account.clone(), 0, addr, block_number, r, s, v, None);
+		let valid = AccountLinker::validate_unsigned(TransactionSource::External, &call).unwrap();
+		assert_eq!(valid.priority, UnsignedPriority::get());
+		assert_eq!(valid.provides, vec![("AccountLinker", (account.clone(), 0u32)).encode()]);
+		assert_eq!(valid.longevity, 98);
+
+		// A signature of another address is a bad proof
+		let other = gen.generate().address().to_fixed_bytes();
+		let call = crate::Call::link_eth_unsigned(account.clone(), 0, other, block_number, r, s, v, None);
+		assert_eq!(
+			AccountLinker::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::BadProof.into()
+		);
+
+		assert_noop!(
+			AccountLinker::link_eth_unsigned(Origin::signed(account.clone()), account.clone(), 0, addr, block_number, r, s, v, None),
+			BadOrigin
+		);
+		assert_ok!(AccountLinker::link_eth_unsigned(Origin::none(), account.clone(), 0, addr, block_number, r, s, v, None));
+		assert_eq!(AccountLinker::eth_addresses(&account), vec![addr]);
+		assert_eq!(AccountLinker::last_unsigned_link(&account), Some(1));
+
+		// The account can't link unsigned again before `UnsignedLinkInterval` blocks
+		let key_pair = gen.generate();
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+		let addr = key_pair.address().to_fixed_bytes();
+
+		let call = crate::Call::link_eth_unsigned(account.clone(), 1, addr, block_number, r, s, v, None);
+		assert_eq!(
+			AccountLinker::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::ExhaustsResources.into()
+		);
+		assert_noop!(
+			AccountLinker::link_eth_unsigned(Origin::none(), account.clone(), 1, addr, block_number, r, s, v, None),
+			AccountLinkerError::UnsignedLinkTooSoon
+		);
+
+		run_to_block(1 + UnsignedLinkInterval::get());
+		assert_ok!(AccountLinker::validate_unsigned(TransactionSource::External, &call));
+		assert_ok!(AccountLinker::link_eth_unsigned(Origin::none(), account.clone(), 1, addr, block_number, r, s, v, None));
+		assert_eq!(AccountLinker::eth_addresses(&account).len(), 2);
+
+		// Expired link messages are stale
+		run_to_block(block_number);
+		let key_pair = gen.generate();
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+
+		let call = crate::Call::link_eth_unsigned(account.clone(), 2, key_pair.address().to_fixed_bytes(), block_number, r, s, v, None);
+		assert_eq!(
+			AccountLinker::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Stale.into()
+		);
+	});
+}
+
+#[test]
+fn test_link_eth_unsigned_without_funds() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([21u8; 32]);
+		let block_number: u32 = 99;
+
+		let mut gen = Random{};
+		let key_pair = gen.generate();
+		let msg = generate_msg(&account, block_number);
+		let sig = generate_sig(&key_pair, &msg);
+		let (r, s, v) = generate_rsv(&sig);
+		let addr = key_pair.address().to_fixed_bytes();
+
+		// links can't be made for free for an account which doesn't exist
+		let call = crate::Call::link_eth_unsigned(account.clone(), 0, addr, block_number, r, s, v, None);
+		assert_eq!(
+			AccountLinker::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Call.into()
+		);
+		assert_noop!(
+			AccountLinker::link_eth_unsigned(Origin::none(), account.clone(), 0, addr, block_number, r, s, v, None),
+			AccountLinkerError::UnknownAccount
+		);
+
+		// unsigned links don't reserve a deposit, so accounts with only the existential deposit can link
+		let _ = Balances::deposit_creating(&account, ExistentialDeposit::get());
+		assert_eq!(Balances::free_balance(&account), ExistentialDeposit::get());
+		let call = crate::Call::link_eth_unsigned(account.clone(), 0, addr, block_number, r, s, v, None);
+		assert_ok!(AccountLinker::validate_unsigned(TransactionSource::External, &call));
+		assert_ok!(AccountLinker::link_eth_unsigned(Origin::none(), account.clone(), 0, addr, block_number, r, s, v, None));
+		assert_eq!(AccountLinker::eth_addresses(&account), vec![addr]);
+		assert_eq!(AccountLinker::slot_deposits(&account, LinkChain::Eth), vec![0]);
+		assert_eq!(AccountLinker::link_deposit(&account), 0);
+
+		assert_ok!(AccountLinker::unlink_eth(Origin::signed(account.clone()), LinkSlot::Index(0)));
+		assert!(AccountLinker::eth_addresses(&account).is_empty());
+	});
+}
+
+#[test]
+fn test_link_eth_unsigned_per_block() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let block_number: u32 = 99;
+		let mut gen = Random{};
+
+		let calls: Vec<_> = (0..MaxUnsignedLinksPerBlock::get() + 1).map(|i| {
+			let account = AccountId32::from([22 + i as u8; 32]);
+			let _ = Balances::deposit_creating(&account, ExistentialDeposit::get());
+			let key_pair = gen.generate();
+			let msg = generate_msg(&account, block_number);
+			let sig = generate_sig(&key_pair, &msg);
+			let (r, s, v) = generate_rsv(&sig);
+			(account, key_pair.address().to_fixed_bytes(), r, s, v)
+		}).collect();
+
+		let (last, calls) = calls.split_last().unwrap();
+		for (account, addr, r, s, v) in calls {
+			assert_ok!(AccountLinker::link_eth_unsigned(Origin::none(), account.clone(), 0, *addr, block_number, *r, *s, *v, None));
+		}
+		assert_eq!(AccountLinker::unsigned_links_in_block(), MaxUnsignedLinksPerBlock::get());
+
+		// the block is full of unsigned links, whatever the account
+		let (account, addr, r, s, v) = last.clone();
+		let call = crate::Call::link_eth_unsigned(account.clone(), 0, addr, block_number, r, s, v, None);
+		assert_eq!(
+			AccountLinker::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::ExhaustsResources.into()
+		);
+		assert_noop!(
+			AccountLinker::link_eth_unsigned(Origin::none(), account.clone(), 0, addr, block_number, r, s, v, None),
+			AccountLinkerError::TooManyUnsignedLinks
+		);
+
+		run_to_block(2);
+		assert_eq!(AccountLinker::unsigned_links_in_block(), 0);
+		assert_ok!(AccountLinker::validate_unsigned(TransactionSource::External, &call));
+		assert_ok!(AccountLinker::link_eth_unsigned(Origin::none(), account.clone(), 0, addr, block_number, r, s, v, None));
+	});
+}
+
+#[test]
+fn test_link_policy() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([18u8; 32]);
+		let delegate: AccountId32 = AccountId32::from([19u8; 32]);
+		let other: AccountId32 = AccountId32::from([20u8; 32]);
+		let block_number: u32 = 99999;
+
+		let mut gen = Random{};
+		add_link_delegate(account.clone(), delegate.clone());
+
+		let link = |origin: &AccountId32, key_pair: &KeyPair| {
+			let msg = generate_msg(&account, block_number);
+			let sig = generate_sig(key_pair, &msg);
+			let (r, s, v) = generate_rsv(&sig);
+
+			AccountLinker::link_eth(
+				Origin::signed(origin.clone()),
+				account.clone(),
+				0,
+				key_pair.address().to_fixed_bytes(),
+				block_number,
+				r,
+				s,
+				v)
+		};
+
+		// Any origin can submit the link signature
+		assert_ok!(link(&other, &gen.generate()));
+
+		set_link_policy(crate::LinkPolicy::SelfOnly);
+		assert_noop!(link(&other, &gen.generate()), AccountLinkerError::UnauthorizedOrigin);
+		assert_noop!(link(&delegate, &gen.generate()), AccountLinkerError::UnauthorizedOrigin);
+		assert_ok!(link(&account, &gen.generate()));
+
+		set_link_policy(crate::LinkPolicy::SelfOrProxy);
+		assert_noop!(link(&other, &gen.generate()), AccountLinkerError::UnauthorizedOrigin);
+		assert_ok!(link(&delegate, &gen.generate()));
+		assert_ok!(link(&account, &gen.generate()));
+
+		assert_eq!(AccountLinker::link_nonce(&account), 4);
+	});
+}
+
+#[test]
+fn test_link_eth_unsigned_policy() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let pair = sr25519::Pair::from_seed(&[23u8; 32]);
+		let account = AccountId32::from(pair.public());
+		let delegate_pair = sr25519::Pair::from_seed(&[24u8; 32]);
+		let block_number: u32 = 99999;
+		let _ = Balances::deposit_creating(&account, ExistentialDeposit::get());
+
+		add_link_delegate(account.clone(), AccountId32::from(delegate_pair.public()));
+
+		// The link signature is enough under `LinkPolicy::Anyone`
+		let call = unsigned_link_call(&account, 0, block_number, None);
+		assert_ok!(AccountLinker::validate_unsigned(TransactionSource::External, &call));
+		assert_ok!(call.dispatch_bypass_filter(Origin::none()));
+
+		for policy in [crate::LinkPolicy::SelfOnly, crate::LinkPolicy::SelfOrProxy].iter() {
+			set_link_policy(*policy);
+			run_to_block(System::block_number() + UnsignedLinkInterval::get());
+
+			let call = unsigned_link_call(&account, 0, block_number, None);
+			assert_eq!(
+				AccountLinker::validate_unsigned(TransactionSource::External, &call),
+				InvalidTransaction::Call.into()
+			);
+			assert_noop!(call.dispatch_bypass_filter(Origin::none()), AccountLinkerError::UnauthorizedOrigin);
+
+			// Only the account itself can authorise its unsigned links, not its delegates
+			let call = unsigned_link_call(&account, 0, block_number, Some(&delegate_pair));
+			assert_eq!(
+				AccountLinker::validate_unsigned(TransactionSource::External, &call),
+				InvalidTransaction::BadProof.into()
+			);
+			assert_noop!(call.dispatch_bypass_filter(Origin::none()), AccountLinkerError::InvalidPolkadotSignature);
+
+			// The account signature commits to the slot
+			let mut call = unsigned_link_call(&account, 0, block_number, Some(&pair));
+			if let crate::Call::link_eth_unsigned(_, index, ..) = &mut call {
+				*index = 1;
+			}
+			assert_noop!(call.dispatch_bypass_filter(Origin::none()), AccountLinkerError::InvalidPolkadotSignature);
+
+			let call = unsigned_link_call(&account, 0, block_number, Some(&pair));
+			assert_ok!(AccountLinker::validate_unsigned(TransactionSource::External, &call));
+			assert_ok!(call.dispatch_bypass_filter(Origin::none()));
+		}
+
+		assert_eq!(AccountLinker::link_nonce(&account), 3);
+	});
+}
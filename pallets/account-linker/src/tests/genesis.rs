@@ -0,0 +1,210 @@
+use crate::{mock::*, btc::base58::ToBase58, util_tron, GenesisConfig, Releases};
+
+use frame_support::traits::GenesisBuild;
+use sp_runtime::AccountId32;
+
+fn build_genesis(config: GenesisConfig<Test>) -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	GenesisBuild::<Test>::assimilate_storage(&config, &mut storage).unwrap();
+	storage.into()
+}
+
+#[test]
+fn test_genesis_links() {
+	let account: AccountId32 = AccountId32::from([1u8; 32]);
+	let other: AccountId32 = AccountId32::from([2u8; 32]);
+
+	let btc_addrs = vec![
+		b"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_vec(),
+		b"36NvZTcMsMowbt78wPzJaHHWaNiyR73Y4g".to_vec(),
+		b"bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".to_vec(),
+	];
+	let sol_addr = [4u8; 32].to_base58();
+	let cosmos_addr = b"cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu".to_vec();
+	let tron_addr = util_tron::tron_addr(&[5u8; 20]);
+	let ltc_addrs = vec![
+		b"LVuDpNCSSj6pQ7t9Pv6d6sUkLKoqDEVUnJ".to_vec(),
+		b"MJaRnao1s62a2zAKSkmG582KbLKianqb7v".to_vec(),
+		b"ltc1qw508d6qejxtdg4y5r3zarvary0c5xw7kgmn4n9".to_vec(),
+	];
+	let doge_addr = b"DFpN6QqFfUm3gKNaxN6tNcab1FArL9cZLE".to_vec();
+	let chain_addr = hex::encode([6u8; 32]).into_bytes();
+
+	build_genesis(GenesisConfig {
+		eth_links: vec![(account.clone(), vec![[1u8; 20], [2u8; 20]]), (other.clone(), vec![[3u8; 20]])],
+		btc_links: vec![(account.clone(), btc_addrs.clone())],
+		polkadot_links: vec![(account.clone(), vec![other.clone()])],
+		chain_links: vec![
+			(account.clone(), HexEd25519Verifier::CHAIN_ID, vec![chain_addr.clone()]),
+			(other.clone(), SolVerifier::<Test>::CHAIN_ID, vec![sol_addr.clone()]),
+			(account.clone(), CosmosVerifier::<Test, CosmosHrp>::CHAIN_ID, vec![cosmos_addr.clone()]),
+			(other.clone(), TronVerifier::<Test>::CHAIN_ID, vec![tron_addr.clone()]),
+			(account.clone(), LtcVerifier::<Test, LitecoinParams>::CHAIN_ID, ltc_addrs.clone()),
+			(other.clone(), DogeVerifier::<Test, DogecoinParams>::CHAIN_ID, vec![doge_addr.clone()]),
+		],
+	}).execute_with(|| {
+		assert_eq!(crate::StorageVersion::<Test>::get(), Releases::V4_0_0);
+
+		assert_eq!(AccountLinker::eth_addresses(&account), vec![[1u8; 20], [2u8; 20]]);
+		assert_eq!(AccountLinker::eth_addresses(&other), vec![[3u8; 20]]);
+		assert_eq!(AccountLinker::btc_addresses(&account), btc_addrs);
+		assert_eq!(AccountLinker::polkadot_addresses(&account), vec![other.clone()]);
+		assert_eq!(AccountLinker::chain_addresses(HexEd25519Verifier::CHAIN_ID, &account), vec![chain_addr.clone()]);
+		assert_eq!(AccountLinker::chain_addresses(SolVerifier::<Test>::CHAIN_ID, &other), vec![sol_addr.clone()]);
+		assert_eq!(
+			AccountLinker::chain_addresses(CosmosVerifier::<Test, CosmosHrp>::CHAIN_ID, &account),
+			vec![cosmos_addr.clone()]
+		);
+		assert_eq!(AccountLinker::chain_addresses(TronVerifier::<Test>::CHAIN_ID, &other), vec![tron_addr.clone()]);
+		assert_eq!(AccountLinker::chain_addresses(LtcVerifier::<Test, LitecoinParams>::CHAIN_ID, &account), ltc_addrs);
+		assert_eq!(
+			AccountLinker::chain_addresses(DogeVerifier::<Test, DogecoinParams>::CHAIN_ID, &other),
+			vec![doge_addr.clone()]
+		);
+
+		assert_eq!(AccountLinker::eth_owners(&[2u8; 20]), vec![account.clone()]);
+		assert_eq!(AccountLinker::btc_owners(&btc_addrs[2]), vec![account.clone()]);
+		assert_eq!(AccountLinker::polkadot_owners(&other), vec![account.clone()]);
+		assert_eq!(AccountLinker::chain_owners(HexEd25519Verifier::CHAIN_ID, &chain_addr), vec![account.clone()]);
+		assert_eq!(AccountLinker::chain_owners(SolVerifier::<Test>::CHAIN_ID, &sol_addr), vec![other.clone()]);
+		assert_eq!(AccountLinker::chain_owners(CosmosVerifier::<Test, CosmosHrp>::CHAIN_ID, &cosmos_addr), vec![account.clone()]);
+		assert_eq!(AccountLinker::chain_owners(TronVerifier::<Test>::CHAIN_ID, &tron_addr), vec![other.clone()]);
+		assert_eq!(
+			AccountLinker::chain_owners(LtcVerifier::<Test, LitecoinParams>::CHAIN_ID, &ltc_addrs[2]),
+			vec![account.clone()]
+		);
+		assert_eq!(AccountLinker::chain_owners(DogeVerifier::<Test, DogecoinParams>::CHAIN_ID, &doge_addr), vec![other.clone()]);
+		assert!(AccountLinker::chain_owners(PrefixedHexEd25519Verifier::CHAIN_ID, &chain_addr).is_empty());
+	});
+}
+
+#[test]
+#[should_panic(expected = "Too many eth links of an account")]
+fn test_genesis_too_many_links() {
+	let account: AccountId32 = AccountId32::from([1u8; 32]);
+
+	build_genesis(GenesisConfig {
+		eth_links: vec![(account, (0..4u8).map(|i| [i; 20]).collect())],
+		btc_links: vec![],
+		polkadot_links: vec![],
+		chain_links: vec![],
+	});
+}
+
+#[test]
+#[should_panic(expected = "Invalid btc address")]
+fn test_genesis_invalid_btc_address() {
+	let account: AccountId32 = AccountId32::from([1u8; 32]);
+
+	// a testnet address on mainnet
+	build_genesis(GenesisConfig {
+		eth_links: vec![],
+		btc_links: vec![(account, vec![b"2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2".to_vec()])],
+		polkadot_links: vec![],
+		chain_links: vec![],
+	});
+}
+
+#[test]
+#[should_panic(expected = "Invalid btc address")]
+fn test_genesis_invalid_btc_checksum() {
+	let account: AccountId32 = AccountId32::from([1u8; 32]);
+
+	build_genesis(GenesisConfig {
+		eth_links: vec![],
+		btc_links: vec![(account, vec![b"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb".to_vec()])],
+		polkadot_links: vec![],
+		chain_links: vec![],
+	});
+}
+
+#[test]
+#[should_panic(expected = "Eth address is linked to more than one account")]
+fn test_genesis_shared_link() {
+	let account: AccountId32 = AccountId32::from([1u8; 32]);
+	let other: AccountId32 = AccountId32::from([2u8; 32]);
+
+	build_genesis(GenesisConfig {
+		eth_links: vec![(account, vec![[1u8; 20]]), (other, vec![[1u8; 20]])],
+		btc_links: vec![],
+		polkadot_links: vec![],
+		chain_links: vec![],
+	});
+}
+
+#[test]
+#[should_panic(expected = "Btc address is linked twice to an account")]
+fn test_genesis_duplicate_link() {
+	let account: AccountId32 = AccountId32::from([1u8; 32]);
+	let addr = b"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_vec();
+
+	build_genesis(GenesisConfig {
+		eth_links: vec![],
+		btc_links: vec![(account, vec![addr.clone(), addr])],
+		polkadot_links: vec![],
+		chain_links: vec![],
+	});
+}
+
+#[test]
+#[should_panic(expected = "Invalid chain address")]
+fn test_genesis_invalid_cosmos_address() {
+	let account: AccountId32 = AccountId32::from([1u8; 32]);
+
+	// an Osmosis address on a chain linking Cosmos Hub addresses
+	build_genesis(GenesisConfig {
+		eth_links: vec![],
+		btc_links: vec![],
+		polkadot_links: vec![],
+		chain_links: vec![(
+			account,
+			CosmosVerifier::<Test, CosmosHrp>::CHAIN_ID,
+			vec![b"osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw".to_vec()]
+		)],
+	});
+}
+
+#[test]
+#[should_panic(expected = "Invalid chain address")]
+fn test_genesis_invalid_ltc_address() {
+	let account: AccountId32 = AccountId32::from([1u8; 32]);
+
+	// a btc address
+	build_genesis(GenesisConfig {
+		eth_links: vec![],
+		btc_links: vec![],
+		polkadot_links: vec![],
+		chain_links: vec![(
+			account,
+			LtcVerifier::<Test, LitecoinParams>::CHAIN_ID,
+			vec![b"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_vec()]
+		)],
+	});
+}
+
+#[test]
+#[should_panic(expected = "Invalid chain address")]
+fn test_genesis_invalid_chain_address() {
+	let account: AccountId32 = AccountId32::from([1u8; 32]);
+
+	// uppercase hex
+	build_genesis(GenesisConfig {
+		eth_links: vec![],
+		btc_links: vec![],
+		polkadot_links: vec![],
+		chain_links: vec![(account, HexEd25519Verifier::CHAIN_ID, vec![hex::encode_upper([6u8; 32]).into_bytes()])],
+	});
+}
+
+#[test]
+#[should_panic(expected = "Unknown chain id")]
+fn test_genesis_unknown_chain() {
+	let account: AccountId32 = AccountId32::from([1u8; 32]);
+
+	build_genesis(GenesisConfig {
+		eth_links: vec![],
+		btc_links: vec![],
+		polkadot_links: vec![],
+		chain_links: vec![(account, 7, vec![hex::encode([6u8; 32]).into_bytes()])],
+	});
+}
@@ -0,0 +1,70 @@
+use crate::{mock::*, Releases};
+
+use codec::Encode;
+use frame_support::{assert_ok, storage::unhashed, traits::{Get, OnRuntimeUpgrade}};
+use sp_runtime::AccountId32;
+
+#[test]
+fn test_migrate_to_bounded_links() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([1u8; 32]);
+		let other: AccountId32 = AccountId32::from([2u8; 32]);
+
+		// links written by the previous version of the pallet
+		let eth_addrs: Vec<[u8; 20]> = (0..5u8).map(|i| [i; 20]).collect();
+		let btc_addrs: Vec<Vec<u8>> = vec![b"1FvyAqqELFiQyaEWdhFbWF8MZapKPZS8J7".to_vec()];
+		let polkadot_addrs: Vec<AccountId32> = vec![other.clone(), account.clone()];
+
+		unhashed::put_raw(&crate::EthereumLink::<Test>::hashed_key_for(&account), &eth_addrs.encode());
+		unhashed::put_raw(&crate::BitcoinLink::<Test>::hashed_key_for(&account), &btc_addrs.encode());
+		unhashed::put_raw(&crate::PolkadotLink::<Test>::hashed_key_for(&account), &polkadot_addrs.encode());
+
+		assert_eq!(crate::StorageVersion::<Test>::get(), Releases::V1_0_0);
+
+		AccountLinker::on_runtime_upgrade();
+
+		assert_eq!(crate::StorageVersion::<Test>::get(), Releases::V4_0_0);
+		// links beyond the limit are dropped
+		assert_eq!(AccountLinker::eth_addresses(&account), eth_addrs[..MaxEthLinks::get() as usize].to_vec());
+		assert_eq!(AccountLinker::btc_addresses(&account), btc_addrs);
+		assert_eq!(AccountLinker::polkadot_addresses(&account), polkadot_addrs);
+		assert!(AccountLinker::eth_addresses(&other).is_empty());
+
+		// linked addresses are indexed by their owner
+		assert_eq!(AccountLinker::eth_owners(&eth_addrs[0]), vec![account.clone()]);
+		assert!(AccountLinker::eth_owners(&eth_addrs[4]).is_empty());
+		assert_eq!(AccountLinker::btc_owners(&btc_addrs[0]), vec![account.clone()]);
+		assert_eq!(AccountLinker::polkadot_owners(&other), vec![account.clone()]);
+
+		// the migration only runs once
+		AccountLinker::on_runtime_upgrade();
+		assert_eq!(AccountLinker::eth_addresses(&account), eth_addrs[..MaxEthLinks::get() as usize].to_vec());
+	});
+}
+
+#[test]
+fn test_migrate_polkadot_pending() {
+	new_test_ext().execute_with(|| {
+
+		let origin: AccountId32 = AccountId32::from([1u8; 32]);
+		let target: AccountId32 = AccountId32::from([2u8; 32]);
+
+		// pending request written by the previous version of the pallet
+		let mut key = sp_io::hashing::twox_128(b"AccountLinker").to_vec();
+		key.extend_from_slice(&sp_io::hashing::twox_128(b"PolkadotPending"));
+		key.extend_from_slice(&sp_io::hashing::blake2_128(&origin.encode()));
+		key.extend_from_slice(&origin.encode());
+		unhashed::put_raw(&key, &(target.clone(), 2u32).encode());
+		crate::StorageVersion::<Test>::put(Releases::V3_0_0);
+
+		AccountLinker::on_runtime_upgrade();
+
+		assert_eq!(crate::StorageVersion::<Test>::get(), Releases::V4_0_0);
+		assert_eq!(AccountLinker::polkadot_pending(&origin, &target), Some((2, crate::EXPIRING_BLOCK_NUMBER_MAX - 1)));
+		assert_eq!(AccountLinker::polkadot_pending_links(&origin)[0].deposit, 0);
+
+		assert_ok!(AccountLinker::accept_polkadot(Origin::signed(target.clone()), origin.clone()));
+		assert_eq!(AccountLinker::polkadot_addresses(&origin), vec![target]);
+	});
+}
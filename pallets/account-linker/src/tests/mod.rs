@@ -1,3 +1,10 @@
 mod btc;
+mod cosmos;
 mod eth;
-mod polkadot;
\ No newline at end of file
+mod genesis;
+mod migration;
+mod polkadot;
+mod sol;
+mod tron;
+mod utxo;
+mod verifier;
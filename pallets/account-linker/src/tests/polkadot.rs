@@ -1,7 +1,8 @@
-use crate::{mock::*};
+use crate::{mock::*, LinkSlot};
 
-use frame_support::{assert_ok, assert_noop};
-use sp_runtime::AccountId32;
+use frame_support::{assert_ok, assert_noop, traits::{Currency, Get}};
+use sp_core::{ecdsa, ed25519, sr25519, Pair};
+use sp_runtime::{traits::IdentifyAccount, AccountId32, MultiSignature, MultiSigner};
 
 #[test]
 fn test_insert_polkadot_address() {
@@ -15,10 +16,17 @@ fn test_insert_polkadot_address() {
         assert_ok!(AccountLinker::link_polkadot(
             Origin::signed(origin.clone()),
             target.clone(),
-            0
+            0,
+            99999
         ));
 
-        assert_eq!(AccountLinker::polkadot_pending(&origin), (target.clone(), 0));
+        assert_eq!(AccountLinker::polkadot_pending(&origin, &target), Some((0, 99999)));
+        assert_eq!(
+                events(),
+                [
+                    Event::account_linker(crate::Event::PolkadotLinkRequested(origin.clone(), target.clone(), 99999)),
+                ]
+            );
 
         assert_ok!(AccountLinker::accept_polkadot(
             Origin::signed(target.clone()),
@@ -28,7 +36,7 @@ fn test_insert_polkadot_address() {
         assert_eq!(
                 events(),
                 [
-                    Event::account_linker(crate::Event::PolkadotAddressLinked(origin.clone(), target.clone())),
+                    Event::account_linker(crate::Event::PolkadotAddressLinked(origin.clone(), target.clone(), 0, None)),
                 ]
             );
 
@@ -45,7 +53,7 @@ fn test_no_polkadot_pending_address() {
         let origin: AccountId32 = AccountId32::from([0u8; 32]);
         let target: AccountId32 = AccountId32::from([1u8; 32]);
 
-        assert_eq!(AccountLinker::polkadot_pending(&origin), (AccountId32::default(), u32::default()));
+        assert_eq!(AccountLinker::polkadot_pending(&origin, &target), None);
 
 		assert_noop!(
             AccountLinker::accept_polkadot(
@@ -55,4 +63,536 @@ fn test_no_polkadot_pending_address() {
 			AccountLinkerError::WrongPendingRequest
 		);
 	});
-}
\ No newline at end of file
+}
+#[test]
+fn test_unlink_polkadot_address() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let origin: AccountId32 = AccountId32::from([0u8; 32]);
+        let target: AccountId32 = AccountId32::from([1u8; 32]);
+
+        assert_ok!(AccountLinker::link_polkadot(
+            Origin::signed(origin.clone()),
+            target.clone(),
+            0,
+            99999
+        ));
+        assert_ok!(AccountLinker::accept_polkadot(
+            Origin::signed(target.clone()),
+            origin.clone()
+        ));
+        events();
+
+        assert_noop!(
+            AccountLinker::unlink_polkadot(Origin::signed(origin.clone()), LinkSlot::Address(origin.clone())),
+            AccountLinkerError::LinkNotFound
+        );
+
+        assert_ok!(AccountLinker::unlink_polkadot(
+            Origin::signed(origin.clone()),
+            LinkSlot::Address(target.clone())
+        ));
+
+        assert_eq!(
+                events(),
+                [
+                    Event::account_linker(crate::Event::PolkadotAddressUnlinked(origin.clone(), target.clone())),
+                ]
+            );
+
+		assert_eq!(AccountLinker::polkadot_addresses(&origin), Vec::<AccountId32>::new());
+	});
+}
+
+#[test]
+fn test_polkadot_owner() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let origin: AccountId32 = AccountId32::from([0u8; 32]);
+        let target: AccountId32 = AccountId32::from([1u8; 32]);
+        let other: AccountId32 = AccountId32::from([2u8; 32]);
+
+        assert_ok!(AccountLinker::link_polkadot(
+            Origin::signed(origin.clone()),
+            target.clone(),
+            0,
+            99999
+        ));
+        assert_ok!(AccountLinker::accept_polkadot(
+            Origin::signed(target.clone()),
+            origin.clone()
+        ));
+
+        assert_eq!(AccountLinker::polkadot_owners(&target), vec![origin.clone()]);
+        assert_eq!(AccountLinker::polkadot_pending(&origin, &target), None);
+
+        // target is already linked to origin
+        assert_ok!(AccountLinker::link_polkadot(
+            Origin::signed(other.clone()),
+            target.clone(),
+            0,
+            99999
+        ));
+        assert_noop!(
+            AccountLinker::accept_polkadot(
+                Origin::signed(target.clone()),
+                other.clone()
+            ),
+            AccountLinkerError::AddressAlreadyLinked
+        );
+
+        assert_ok!(AccountLinker::unlink_polkadot(
+            Origin::signed(origin.clone()),
+            LinkSlot::Index(0)
+        ));
+        assert!(AccountLinker::polkadot_owners(&target).is_empty());
+
+        assert_ok!(AccountLinker::accept_polkadot(
+            Origin::signed(target.clone()),
+            other.clone()
+        ));
+        assert_eq!(AccountLinker::polkadot_owners(&target), vec![other.clone()]);
+        assert_eq!(AccountLinker::polkadot_addresses(&other), vec![target]);
+	});
+}
+
+fn sign_link_message<P: Pair>(pair: &P, account: &AccountId32, block_number: u32) -> (AccountId32, MultiSignature)
+where
+    MultiSigner: From<P::Public>,
+    MultiSignature: From<P::Signature>,
+{
+    let target = MultiSigner::from(pair.public()).into_account();
+    let sig = pair.sign(&AccountLinker::link_message(account, block_number));
+    (target, MultiSignature::from(sig))
+}
+
+#[test]
+fn test_link_polkadot_signed() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let account: AccountId32 = AccountId32::from([0u8; 32]);
+        let block_number: u32 = 99999;
+        let mut expected_vec = Vec::new();
+
+        // sr25519, ed25519 and ecdsa accounts
+        for i in 0..3u32 {
+            let (target, signature) = match i {
+                0 => sign_link_message(&sr25519::Pair::from_seed(&[1u8; 32]), &account, block_number),
+                1 => sign_link_message(&ed25519::Pair::from_seed(&[2u8; 32]), &account, block_number),
+                _ => sign_link_message(&ecdsa::Pair::from_seed(&[3u8; 32]), &account, block_number),
+            };
+
+            assert_ok!(AccountLinker::link_polkadot_signed(
+                Origin::signed(account.clone()),
+                account.clone(),
+                i,
+                target.clone(),
+                block_number,
+                signature
+            ));
+
+            assert_eq!(
+                events(),
+                [
+                    Event::account_linker(crate::Event::PolkadotAddressLinked(account.clone(), target.clone(), i, None)),
+                ]
+            );
+            expected_vec.push(target);
+        }
+
+        assert_eq!(AccountLinker::polkadot_addresses(&account), expected_vec);
+        assert_eq!(AccountLinker::polkadot_owners(&expected_vec[0]), vec![account.clone()]);
+	});
+}
+
+#[test]
+fn test_link_polkadot_signed_wrapped_bytes() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let account: AccountId32 = AccountId32::from([0u8; 32]);
+        let block_number: u32 = 99999;
+
+        let pair = sr25519::Pair::from_seed(&[1u8; 32]);
+        let target = MultiSigner::from(pair.public()).into_account();
+
+        let mut msg = b"<Bytes>".to_vec();
+        msg.extend_from_slice(&AccountLinker::link_message(&account, block_number));
+        msg.extend_from_slice(b"</Bytes>");
+        let signature = MultiSignature::from(pair.sign(&msg));
+
+        assert_ok!(AccountLinker::link_polkadot_signed(
+            Origin::signed(account.clone()),
+            account.clone(),
+            0,
+            target.clone(),
+            block_number,
+            signature
+        ));
+
+        assert_eq!(AccountLinker::polkadot_addresses(&account), vec![target]);
+	});
+}
+
+#[test]
+fn test_invalid_polkadot_signature() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let account: AccountId32 = AccountId32::from([0u8; 32]);
+        let other: AccountId32 = AccountId32::from([1u8; 32]);
+        let block_number: u32 = 99999;
+
+        let (target, signature) = sign_link_message(&sr25519::Pair::from_seed(&[1u8; 32]), &account, block_number);
+
+        // signed for another expiring block number
+        assert_noop!(
+            AccountLinker::link_polkadot_signed(
+                Origin::signed(account.clone()),
+                account.clone(),
+                0,
+                target.clone(),
+                block_number + 1,
+                signature.clone()
+            ),
+            AccountLinkerError::InvalidPolkadotSignature
+        );
+
+        // signed by another account
+        assert_noop!(
+            AccountLinker::link_polkadot_signed(
+                Origin::signed(account.clone()),
+                account.clone(),
+                0,
+                other.clone(),
+                block_number,
+                signature.clone()
+            ),
+            AccountLinkerError::InvalidPolkadotSignature
+        );
+
+        assert_ok!(AccountLinker::link_polkadot_signed(
+            Origin::signed(account.clone()),
+            account.clone(),
+            0,
+            target.clone(),
+            block_number,
+            signature.clone()
+        ));
+
+        // the signature commits to the nonce, so it can't be replayed
+        assert_noop!(
+            AccountLinker::link_polkadot_signed(
+                Origin::signed(account.clone()),
+                account.clone(),
+                1,
+                target.clone(),
+                block_number,
+                signature
+            ),
+            AccountLinkerError::InvalidPolkadotSignature
+        );
+	});
+}
+
+#[test]
+fn test_multiple_polkadot_pending() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let origin: AccountId32 = AccountId32::from([0u8; 32]);
+        let target: AccountId32 = AccountId32::from([1u8; 32]);
+        let other: AccountId32 = AccountId32::from([2u8; 32]);
+
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 99999));
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), other.clone(), 1, 99999));
+        // a new request for the same target replaces the previous one
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 1, 88888));
+
+        assert_eq!(AccountLinker::polkadot_pending(&origin, &target), Some((1, 88888)));
+        assert_eq!(AccountLinker::polkadot_pending(&origin, &other), Some((1, 99999)));
+
+        assert_ok!(AccountLinker::accept_polkadot(Origin::signed(other.clone()), origin.clone()));
+        assert_ok!(AccountLinker::accept_polkadot(Origin::signed(target.clone()), origin.clone()));
+
+        assert_eq!(AccountLinker::polkadot_addresses(&origin), vec![other, target]);
+	});
+}
+
+#[test]
+fn test_cancel_polkadot_link() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let origin: AccountId32 = AccountId32::from([0u8; 32]);
+        let target: AccountId32 = AccountId32::from([1u8; 32]);
+
+        assert_noop!(
+            AccountLinker::cancel_polkadot_link(Origin::signed(origin.clone()), target.clone()),
+            AccountLinkerError::WrongPendingRequest
+        );
+
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 99999));
+        events();
+
+        assert_ok!(AccountLinker::cancel_polkadot_link(Origin::signed(origin.clone()), target.clone()));
+        assert_eq!(
+                events(),
+                [
+                    Event::account_linker(crate::Event::PolkadotLinkCancelled(origin.clone(), target.clone())),
+                ]
+            );
+        assert_eq!(AccountLinker::polkadot_pending(&origin, &target), None);
+
+        assert_noop!(
+            AccountLinker::accept_polkadot(Origin::signed(target.clone()), origin.clone()),
+            AccountLinkerError::WrongPendingRequest
+        );
+	});
+}
+
+#[test]
+fn test_polkadot_pending_expired() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let origin: AccountId32 = AccountId32::from([0u8; 32]);
+        let target: AccountId32 = AccountId32::from([1u8; 32]);
+
+        assert_noop!(
+            AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 1),
+            AccountLinkerError::LinkRequestExpired
+        );
+        assert_noop!(
+            AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, crate::EXPIRING_BLOCK_NUMBER_MAX + 1),
+            AccountLinkerError::InvalidExpiringBlockNumber
+        );
+
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 5));
+        events();
+
+        run_to_block(4);
+        assert_eq!(AccountLinker::polkadot_pending(&origin, &target), Some((0, 5)));
+        assert!(events().is_empty());
+
+        run_to_block(5);
+        assert_eq!(AccountLinker::polkadot_pending(&origin, &target), None);
+        assert_eq!(
+                events(),
+                [
+                    Event::account_linker(crate::Event::PolkadotLinkExpired(origin.clone(), target.clone())),
+                ]
+            );
+
+        assert_noop!(
+            AccountLinker::accept_polkadot(Origin::signed(target.clone()), origin.clone()),
+            AccountLinkerError::WrongPendingRequest
+        );
+	});
+}
+
+#[test]
+fn test_polkadot_link_deposit() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let origin: AccountId32 = AccountId32::from([0u8; 32]);
+        let target: AccountId32 = AccountId32::from([1u8; 32]);
+
+        // a link made without a deposit
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 99999));
+        assert_ok!(AccountLinker::accept_polkadot(Origin::signed(target.clone()), origin.clone()));
+
+        set_link_deposit(10);
+        let _ = Balances::deposit_creating(&origin, 100);
+
+        let (other, signature) = sign_link_message(&sr25519::Pair::from_seed(&[1u8; 32]), &origin, 99999);
+        assert_ok!(AccountLinker::link_polkadot_signed(
+            Origin::signed(origin.clone()),
+            origin.clone(),
+            1,
+            other.clone(),
+            99999,
+            signature
+        ));
+        assert_eq!(Balances::reserved_balance(&origin), 10);
+        assert_eq!(AccountLinker::link_deposit(&origin), 10);
+
+        assert_ok!(AccountLinker::unlink_polkadot(Origin::signed(origin.clone()), LinkSlot::Address(other)));
+        assert_eq!(Balances::reserved_balance(&origin), 0);
+        // only the held deposit is returned
+        assert_ok!(AccountLinker::unlink_polkadot(Origin::signed(origin.clone()), LinkSlot::Address(target)));
+        assert_eq!(Balances::reserved_balance(&origin), 0);
+        assert_eq!(Balances::free_balance(&origin), 100);
+	});
+}
+
+#[test]
+fn test_polkadot_link_changes() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let origin: AccountId32 = AccountId32::from([0u8; 32]);
+        let target: AccountId32 = AccountId32::from([1u8; 32]);
+        let other: AccountId32 = AccountId32::from([2u8; 32]);
+
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 99999));
+        assert_ok!(AccountLinker::accept_polkadot(Origin::signed(target.clone()), origin.clone()));
+        assert_eq!(link_changes(), vec![LinkChange::Linked(origin.clone(), ExternalAddress::Polkadot(target.clone()))]);
+
+        // replacing the linked account unlinks it
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), other.clone(), 0, 99999));
+        assert_ok!(AccountLinker::accept_polkadot(Origin::signed(other.clone()), origin.clone()));
+        assert_eq!(
+            link_changes(),
+            vec![
+                LinkChange::Unlinked(origin.clone(), ExternalAddress::Polkadot(target.clone())),
+                LinkChange::Linked(origin.clone(), ExternalAddress::Polkadot(other.clone())),
+            ]
+        );
+
+        assert_ok!(AccountLinker::unlink_polkadot(Origin::signed(origin.clone()), LinkSlot::Index(0)));
+        assert_eq!(link_changes(), vec![LinkChange::Unlinked(origin.clone(), ExternalAddress::Polkadot(other.clone()))]);
+	});
+}
+
+#[test]
+fn test_too_many_polkadot_pending() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let origin: AccountId32 = AccountId32::from([0u8; 32]);
+        let targets: Vec<AccountId32> = (1..=MaxPendingPerOrigin::get()).map(|i| AccountId32::from([i as u8; 32])).collect();
+
+        for target in targets.iter() {
+            assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 99999));
+        }
+        assert_noop!(
+            AccountLinker::link_polkadot(Origin::signed(origin.clone()), AccountId32::from([9u8; 32]), 0, 99999),
+            AccountLinkerError::TooManyPendingLinks
+        );
+
+        // a pending request can still be replaced
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), targets[0].clone(), 1, 88888));
+        assert_eq!(AccountLinker::polkadot_pending(&origin, &targets[0]), Some((1, 88888)));
+        assert_eq!(AccountLinker::polkadot_pending_links(&origin).len(), MaxPendingPerOrigin::get() as usize);
+	});
+}
+
+#[test]
+fn test_polkadot_request_deposit() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let origin: AccountId32 = AccountId32::from([0u8; 32]);
+        let target: AccountId32 = AccountId32::from([1u8; 32]);
+        let other: AccountId32 = AccountId32::from([2u8; 32]);
+
+        set_link_deposit(10);
+        let _ = Balances::deposit_creating(&origin, 100);
+
+        // the deposit of a request is kept when it is replaced
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 99999));
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 88888));
+        assert_eq!(Balances::reserved_balance(&origin), 10);
+
+        assert_ok!(AccountLinker::cancel_polkadot_link(Origin::signed(origin.clone()), target.clone()));
+        assert_eq!(Balances::reserved_balance(&origin), 0);
+
+        // the deposit of the accepted request is replaced by the deposit of the link
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 99999));
+        assert_ok!(AccountLinker::accept_polkadot(Origin::signed(target.clone()), origin.clone()));
+        assert_eq!(Balances::reserved_balance(&origin), 10);
+        assert_eq!(AccountLinker::link_deposit(&origin), 10);
+
+        // the deposit of an expired request is returned
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), other.clone(), 1, 5));
+        assert_eq!(Balances::reserved_balance(&origin), 20);
+        run_to_block(5);
+        assert_eq!(Balances::reserved_balance(&origin), 10);
+
+        // requests can't be made without the deposit
+        assert_noop!(
+            AccountLinker::link_polkadot(Origin::signed(other.clone()), target.clone(), 0, 99999),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+	});
+}
+
+#[test]
+fn test_polkadot_pending_expiries_carried_over() {
+	new_test_ext().execute_with(|| {
+
+        run_to_block(1);
+
+        let target: AccountId32 = AccountId32::from([0u8; 32]);
+        let origins: Vec<AccountId32> = (1..=5u8).map(|i| AccountId32::from([i; 32])).collect();
+
+        for origin in origins.iter() {
+            assert_ok!(AccountLinker::link_polkadot(Origin::signed(origin.clone()), target.clone(), 0, 5));
+        }
+        events();
+
+        let pending = || origins.iter().filter(|origin| AccountLinker::polkadot_pending(origin, &target).is_some()).count();
+
+        // `MaxPendingExpiriesPerBlock` requests are pruned in each block
+        run_to_block(5);
+        assert_eq!(pending(), 3);
+        assert_eq!(events().len(), 2);
+        run_to_block(6);
+        assert_eq!(pending(), 1);
+        run_to_block(7);
+        assert_eq!(pending(), 0);
+        assert_eq!(events().len(), 3);
+
+        // later requests are pruned at their expiry
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origins[0].clone()), target.clone(), 0, 9));
+        run_to_block(9);
+        assert_eq!(pending(), 0);
+	});
+}
+
+#[test]
+fn test_polkadot_pending_expiries_catch_up() {
+	new_test_ext().execute_with(|| {
+
+        set_max_pending_expiries_per_block(1);
+        run_to_block(1);
+
+        let target: AccountId32 = AccountId32::from([0u8; 32]);
+        let origins: Vec<AccountId32> = (1..=3u8).map(|i| AccountId32::from([i; 32])).collect();
+
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origins[0].clone()), target.clone(), 0, 5));
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origins[1].clone()), target.clone(), 0, 5));
+        assert_ok!(AccountLinker::link_polkadot(Origin::signed(origins[2].clone()), target.clone(), 0, 8));
+
+        let pending = || origins.iter().filter(|origin| AccountLinker::polkadot_pending(origin, &target).is_some()).count();
+
+        // the second expiry of block 5 is carried over to block 6
+        run_to_block(5);
+        assert_eq!(pending(), 2);
+        run_to_block(6);
+        assert_eq!(pending(), 1);
+
+        // empty blocks don't use up the budget, so the cursor catches up and prunes the request at its expiry
+        run_to_block(7);
+        assert_eq!(pending(), 1);
+        run_to_block(8);
+        assert_eq!(pending(), 0);
+	});
+}
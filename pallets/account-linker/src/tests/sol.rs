@@ -0,0 +1,189 @@
+use crate::{mock::*, btc::base58::ToBase58, LinkSlot};
+
+use frame_support::{assert_ok, assert_noop, traits::Currency};
+use sp_core::{ed25519, Pair};
+use sp_runtime::AccountId32;
+
+// The signature of the link message by the key
+fn sign_link_message(pair: &ed25519::Pair, account: &AccountId32, block_number: u32) -> Vec<u8> {
+	pair.sign(&AccountLinker::link_message(account, block_number)).0.to_vec()
+}
+
+#[test]
+fn test_link_sol() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([1u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = SolVerifier::<Test>::CHAIN_ID;
+
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let addr = pair.public().0.to_base58();
+		let sig = sign_link_message(&pair, &account, block_number);
+
+		let _ = Balances::deposit_creating(&account, 100);
+		events();
+
+		assert_ok!(AccountLinker::link(
+			Origin::signed(account.clone()),
+			chain_id,
+			account.clone(),
+			0,
+			addr.clone(),
+			block_number,
+			sig.clone()
+		));
+
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![addr.clone()]);
+		assert_eq!(AccountLinker::chain_owners(chain_id, &addr), vec![account.clone()]);
+		assert_eq!(AccountLinker::link_nonce(&account), 1);
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(
+			events(),
+			[
+				Event::pallet_balances(pallet_balances::Event::Reserved(account.clone(), 10)),
+				Event::account_linker(crate::Event::AddressLinked(account.clone(), chain_id, addr.clone(), 0, None)),
+			]
+		);
+
+		// The signature commits to the nonce, so it can't be replayed
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 1, addr, block_number, sig),
+			AccountLinkerError::InvalidSolSignature
+		);
+	});
+}
+
+#[test]
+fn test_link_sol_invalid_signature() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([2u8; 32]);
+		let other: AccountId32 = AccountId32::from([3u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = SolVerifier::<Test>::CHAIN_ID;
+
+		let pair = ed25519::Pair::from_seed(&[2u8; 32]);
+		let addr = pair.public().0.to_base58();
+
+		// signed for another account
+		let sig = sign_link_message(&pair, &other, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr.clone(), block_number, sig),
+			AccountLinkerError::InvalidSolSignature
+		);
+
+		// signed by another key
+		let pair = ed25519::Pair::from_seed(&[3u8; 32]);
+		let sig = sign_link_message(&pair, &account, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 0, addr, block_number, sig),
+			AccountLinkerError::InvalidSolSignature
+		);
+	});
+}
+
+#[test]
+fn test_link_sol_expired() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([4u8; 32]);
+		let chain_id = SolVerifier::<Test>::CHAIN_ID;
+
+		let pair = ed25519::Pair::from_seed(&[4u8; 32]);
+		let addr = pair.public().0.to_base58();
+		let sig = sign_link_message(&pair, &account, 1);
+
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 0, addr, 1, sig),
+			AccountLinkerError::LinkRequestExpired
+		);
+	});
+}
+
+#[test]
+fn test_unlink_sol() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([5u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = SolVerifier::<Test>::CHAIN_ID;
+
+		let _ = Balances::deposit_creating(&account, 100);
+
+		let mut linked = Vec::new();
+		for i in 0..2u8 {
+			let pair = ed25519::Pair::from_seed(&[10 + i; 32]);
+			let addr = pair.public().0.to_base58();
+			let sig = sign_link_message(&pair, &account, block_number);
+			assert_ok!(AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), i as u32, addr.clone(), block_number, sig));
+			linked.push(addr);
+		}
+		assert_eq!(Balances::reserved_balance(&account), 20);
+		events();
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Address(linked[0].clone())));
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![linked[1].clone()]);
+		assert!(AccountLinker::chain_owners(chain_id, &linked[0]).is_empty());
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(
+			events(),
+			[
+				Event::pallet_balances(pallet_balances::Event::Unreserved(account.clone(), 10)),
+				Event::account_linker(crate::Event::AddressUnlinked(account.clone(), chain_id, linked[0].clone())),
+			]
+		);
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Index(0)));
+		assert!(AccountLinker::chain_addresses(chain_id, &account).is_empty());
+		assert_eq!(Balances::reserved_balance(&account), 0);
+		assert_eq!(Balances::free_balance(&account), 100);
+
+		assert_noop!(
+			AccountLinker::unlink(Origin::signed(account), chain_id, LinkSlot::Index(0)),
+			AccountLinkerError::LinkNotFound
+		);
+	});
+}
+
+#[test]
+fn test_link_sol_invalid_address() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([4u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = SolVerifier::<Test>::CHAIN_ID;
+
+		let pair = ed25519::Pair::from_seed(&[4u8; 32]);
+		let addr = pair.public().0.to_base58();
+		let sig = sign_link_message(&pair, &account, block_number);
+
+		let mut invalid_addrs = vec![
+			// not base58
+			b"0OIl".to_vec(),
+			// 31 bytes
+			pair.public().0[1..].to_base58(),
+		];
+		// 33 bytes, the leading `1` is a zero byte
+		let mut too_long = addr;
+		too_long.insert(0, b'1');
+		invalid_addrs.push(too_long);
+
+		for invalid_addr in invalid_addrs {
+			assert_noop!(
+				AccountLinker::link(
+					Origin::signed(account.clone()), chain_id, account.clone(), 0, invalid_addr, block_number, sig.clone()
+				),
+				AccountLinkerError::InvalidSolAddress
+			);
+		}
+	});
+}
@@ -0,0 +1,230 @@
+use crate::{mock::*, LinkSlot};
+use super::eth::negate_s;
+
+use parity_crypto::Keccak256;
+use parity_crypto::publickey::{KeyPair, Message, sign};
+use frame_support::{assert_ok, assert_noop, traits::Currency};
+use sp_runtime::AccountId32;
+
+use bitcoin::util::base58;
+
+// The base58check Tron address of a key
+fn tron_addr(key_pair: &KeyPair) -> Vec<u8> {
+	let mut payload = vec![0x41];
+	payload.extend_from_slice(key_pair.address().as_bytes());
+	base58::check_encode_slice(&payload).into_bytes()
+}
+
+// The TIP-191 signature of the link message, as `r ++ s ++ v`
+fn sign_link_message(key_pair: &KeyPair, account: &AccountId32, block_number: u32) -> Vec<u8> {
+	let bytes = AccountLinker::link_message(account, block_number);
+	let mut data = format!("\x19TRON Signed Message:\n{}", bytes.len()).into_bytes();
+	data.extend_from_slice(&bytes);
+
+	sign(key_pair.secret(), &Message::from(data.keccak256())).unwrap().into_electrum().to_vec()
+}
+
+#[test]
+fn test_link_tron() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([1u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = TronVerifier::<Test>::CHAIN_ID;
+
+		let key_pair = KeyPair::from_secret_slice(&[1u8; 32]).unwrap();
+		let addr = tron_addr(&key_pair);
+		let sig = sign_link_message(&key_pair, &account, block_number);
+
+		let _ = Balances::deposit_creating(&account, 100);
+		events();
+
+		assert_ok!(AccountLinker::link(
+			Origin::signed(account.clone()),
+			chain_id,
+			account.clone(),
+			0,
+			addr.clone(),
+			block_number,
+			sig.clone()
+		));
+
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![addr.clone()]);
+		assert_eq!(AccountLinker::chain_owners(chain_id, &addr), vec![account.clone()]);
+		assert_eq!(AccountLinker::link_nonce(&account), 1);
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(
+			events(),
+			[
+				Event::pallet_balances(pallet_balances::Event::Reserved(account.clone(), 10)),
+				Event::account_linker(crate::Event::AddressLinked(account.clone(), chain_id, addr.clone(), 0, None)),
+			]
+		);
+
+		// The signature commits to the nonce, so it can't be replayed
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 1, addr, block_number, sig),
+			AccountLinkerError::UnexpectedAddress
+		);
+	});
+}
+
+#[test]
+fn test_link_tron_invalid_signature() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([2u8; 32]);
+		let other: AccountId32 = AccountId32::from([3u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = TronVerifier::<Test>::CHAIN_ID;
+
+		let key_pair = KeyPair::from_secret_slice(&[2u8; 32]).unwrap();
+		let addr = tron_addr(&key_pair);
+
+		// signed for another account
+		let sig = sign_link_message(&key_pair, &other, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr.clone(), block_number, sig),
+			AccountLinkerError::UnexpectedAddress
+		);
+
+		// signed by another key
+		let key_pair = KeyPair::from_secret_slice(&[3u8; 32]).unwrap();
+		let sig = sign_link_message(&key_pair, &account, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 0, addr, block_number, sig),
+			AccountLinkerError::UnexpectedAddress
+		);
+	});
+}
+
+#[test]
+fn test_link_tron_expired() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([4u8; 32]);
+		let chain_id = TronVerifier::<Test>::CHAIN_ID;
+
+		let key_pair = KeyPair::from_secret_slice(&[4u8; 32]).unwrap();
+		let addr = tron_addr(&key_pair);
+		let sig = sign_link_message(&key_pair, &account, 1);
+
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 0, addr, 1, sig),
+			AccountLinkerError::LinkRequestExpired
+		);
+	});
+}
+
+#[test]
+fn test_unlink_tron() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([5u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = TronVerifier::<Test>::CHAIN_ID;
+
+		let _ = Balances::deposit_creating(&account, 100);
+
+		let mut linked = Vec::new();
+		for i in 0..2u8 {
+			let key_pair = KeyPair::from_secret_slice(&[10 + i; 32]).unwrap();
+			let addr = tron_addr(&key_pair);
+			let sig = sign_link_message(&key_pair, &account, block_number);
+			assert_ok!(AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), i as u32, addr.clone(), block_number, sig));
+			linked.push(addr);
+		}
+		assert_eq!(Balances::reserved_balance(&account), 20);
+		events();
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Address(linked[0].clone())));
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![linked[1].clone()]);
+		assert!(AccountLinker::chain_owners(chain_id, &linked[0]).is_empty());
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(
+			events(),
+			[
+				Event::pallet_balances(pallet_balances::Event::Unreserved(account.clone(), 10)),
+				Event::account_linker(crate::Event::AddressUnlinked(account.clone(), chain_id, linked[0].clone())),
+			]
+		);
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Index(0)));
+		assert!(AccountLinker::chain_addresses(chain_id, &account).is_empty());
+		assert_eq!(Balances::reserved_balance(&account), 0);
+		assert_eq!(Balances::free_balance(&account), 100);
+
+		assert_noop!(
+			AccountLinker::unlink(Origin::signed(account), chain_id, LinkSlot::Index(0)),
+			AccountLinkerError::LinkNotFound
+		);
+	});
+}
+
+#[test]
+fn test_link_tron_high_s() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([2u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = TronVerifier::<Test>::CHAIN_ID;
+
+		let key_pair = KeyPair::from_secret_slice(&[2u8; 32]).unwrap();
+		let addr = tron_addr(&key_pair);
+
+		// the high-s form of a valid signature is rejected
+		let mut sig = sign_link_message(&key_pair, &account, block_number);
+		let mut s = [0u8; 32];
+		s.copy_from_slice(&sig[32..64]);
+		sig[32..64].copy_from_slice(&negate_s(&s));
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr, block_number, sig),
+			AccountLinkerError::MalleableEthSignature
+		);
+	});
+}
+
+#[test]
+fn test_link_tron_invalid_address() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([4u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = TronVerifier::<Test>::CHAIN_ID;
+
+		let key_pair = KeyPair::from_secret_slice(&[4u8; 32]).unwrap();
+		let addr = tron_addr(&key_pair);
+		let sig = sign_link_message(&key_pair, &account, block_number);
+
+		let mut btc_payload = vec![0x00];
+		btc_payload.extend_from_slice(key_pair.address().as_bytes());
+		let mut bad_checksum = addr;
+		let last = bad_checksum.len() - 1;
+		bad_checksum[last] = if bad_checksum[last] == b'1' { b'2' } else { b'1' };
+
+		let invalid_addrs = vec![
+			// the btc version byte
+			base58::check_encode_slice(&btc_payload).into_bytes(),
+			// hex instead of base58check
+			hex::encode(key_pair.address().as_bytes()).into_bytes(),
+			bad_checksum,
+		];
+
+		for invalid_addr in invalid_addrs {
+			assert_noop!(
+				AccountLinker::link(
+					Origin::signed(account.clone()), chain_id, account.clone(), 0, invalid_addr, block_number, sig.clone()
+				),
+				AccountLinkerError::InvalidTronAddress
+			);
+		}
+	});
+}
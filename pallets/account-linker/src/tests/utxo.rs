@@ -0,0 +1,495 @@
+use crate::{mock::*, LinkSlot};
+
+use frame_support::{assert_ok, assert_noop, traits::Currency};
+use sp_runtime::AccountId32;
+
+use bitcoin::bech32::{self, u5, ToBase32};
+use bitcoin::hashes::{hash160, sha256d, Hash};
+use bitcoin::secp256k1::{Secp256k1, Message, SecretKey, PublicKey};
+use bitcoin::util::base58;
+
+// The base58check address of a version byte and a 20-byte hash
+fn base58_addr(version: u8, hash: &[u8]) -> Vec<u8> {
+	let mut payload = vec![version];
+	payload.extend_from_slice(hash);
+	base58::check_encode_slice(&payload).into_bytes()
+}
+
+fn pk_hash(secret: &SecretKey) -> Vec<u8> {
+	let pk = PublicKey::from_secret_key(&Secp256k1::new(), secret);
+	hash160::Hash::hash(&pk.serialize()).to_vec()
+}
+
+fn p2pkh_addr(version: u8, secret: &SecretKey) -> Vec<u8> {
+	base58_addr(version, &pk_hash(secret))
+}
+
+fn p2sh_p2wpkh_addr(version: u8, secret: &SecretKey) -> Vec<u8> {
+	let mut redeem_script = vec![0x00, 0x14];
+	redeem_script.extend_from_slice(&pk_hash(secret));
+	base58_addr(version, &hash160::Hash::hash(&redeem_script))
+}
+
+fn p2wpkh_addr(hrp: &str, secret: &SecretKey) -> Vec<u8> {
+	let mut data = vec![u5::try_from_u8(0).unwrap()];
+	data.extend(pk_hash(secret).to_base32());
+	bech32::encode(hrp, data).unwrap().into_bytes()
+}
+
+// The signed message of the link message with the message magic `magic`, as `r ++ s ++ BIP-137 header`
+fn sign_link_message(
+	magic: &[u8],
+	secret: &SecretKey,
+	header_base: u8,
+	account: &AccountId32,
+	block_number: u32,
+) -> Vec<u8> {
+	let bytes = AccountLinker::link_message(account, block_number);
+	// the link message is shorter than 253 bytes so its varint length is one byte
+	let mut data = magic.to_vec();
+	data.push(bytes.len() as u8);
+	data.extend_from_slice(&bytes);
+	let msg = Message::from_slice(&sha256d::Hash::hash(&data)[..]).unwrap();
+
+	let (recovery_id, sig) = Secp256k1::new().sign_recoverable(&msg, secret).serialize_compact();
+
+	let mut signature = sig.to_vec();
+	signature.push(header_base + recovery_id.to_i32() as u8);
+	signature
+}
+
+const LITECOIN_MAGIC: &[u8] = b"\x19Litecoin Signed Message:\n";
+const DOGECOIN_MAGIC: &[u8] = b"\x19Dogecoin Signed Message:\n";
+const BITCOIN_MAGIC: &[u8] = b"\x18Bitcoin Signed Message:\n";
+
+#[test]
+fn test_link_ltc() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([1u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = LtcVerifier::<Test, LitecoinParams>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[1u8; 32]).unwrap();
+		let addr = p2pkh_addr(0x30, &secret);
+		let sig = sign_link_message(LITECOIN_MAGIC, &secret, 31, &account, block_number);
+
+		let _ = Balances::deposit_creating(&account, 100);
+		events();
+
+		assert_ok!(AccountLinker::link(
+			Origin::signed(account.clone()),
+			chain_id,
+			account.clone(),
+			0,
+			addr.clone(),
+			block_number,
+			sig.clone()
+		));
+
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![addr.clone()]);
+		assert_eq!(AccountLinker::chain_owners(chain_id, &addr), vec![account.clone()]);
+		assert_eq!(AccountLinker::link_nonce(&account), 1);
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(
+			events(),
+			[
+				Event::pallet_balances(pallet_balances::Event::Reserved(account.clone(), 10)),
+				Event::account_linker(crate::Event::AddressLinked(account.clone(), chain_id, addr.clone(), 0, None)),
+			]
+		);
+
+		// The signature commits to the nonce, so it can't be replayed
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 1, addr, block_number, sig),
+			AccountLinkerError::UnexpectedAddress
+		);
+	});
+}
+
+#[test]
+fn test_link_ltc_invalid_signature() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([2u8; 32]);
+		let other: AccountId32 = AccountId32::from([3u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = LtcVerifier::<Test, LitecoinParams>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[2u8; 32]).unwrap();
+		let addr = p2pkh_addr(0x30, &secret);
+
+		// signed for another account
+		let sig = sign_link_message(LITECOIN_MAGIC, &secret, 31, &other, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr.clone(), block_number, sig),
+			AccountLinkerError::UnexpectedAddress
+		);
+
+		// signed by another key
+		let secret = SecretKey::from_slice(&[3u8; 32]).unwrap();
+		let sig = sign_link_message(LITECOIN_MAGIC, &secret, 31, &account, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 0, addr, block_number, sig),
+			AccountLinkerError::UnexpectedAddress
+		);
+	});
+}
+
+#[test]
+fn test_link_ltc_expired() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([4u8; 32]);
+		let chain_id = LtcVerifier::<Test, LitecoinParams>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[4u8; 32]).unwrap();
+		let addr = p2pkh_addr(0x30, &secret);
+		let sig = sign_link_message(LITECOIN_MAGIC, &secret, 31, &account, 1);
+
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 0, addr, 1, sig),
+			AccountLinkerError::LinkRequestExpired
+		);
+	});
+}
+
+#[test]
+fn test_unlink_ltc() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([5u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = LtcVerifier::<Test, LitecoinParams>::CHAIN_ID;
+
+		let _ = Balances::deposit_creating(&account, 100);
+
+		let mut linked = Vec::new();
+		for i in 0..2u8 {
+			let secret = SecretKey::from_slice(&[10 + i; 32]).unwrap();
+			let addr = p2pkh_addr(0x30, &secret);
+			let sig = sign_link_message(LITECOIN_MAGIC, &secret, 31, &account, block_number);
+			assert_ok!(AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), i as u32, addr.clone(), block_number, sig));
+			linked.push(addr);
+		}
+		assert_eq!(Balances::reserved_balance(&account), 20);
+		events();
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Address(linked[0].clone())));
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![linked[1].clone()]);
+		assert!(AccountLinker::chain_owners(chain_id, &linked[0]).is_empty());
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(
+			events(),
+			[
+				Event::pallet_balances(pallet_balances::Event::Unreserved(account.clone(), 10)),
+				Event::account_linker(crate::Event::AddressUnlinked(account.clone(), chain_id, linked[0].clone())),
+			]
+		);
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Index(0)));
+		assert!(AccountLinker::chain_addresses(chain_id, &account).is_empty());
+		assert_eq!(Balances::reserved_balance(&account), 0);
+		assert_eq!(Balances::free_balance(&account), 100);
+
+		assert_noop!(
+			AccountLinker::unlink(Origin::signed(account), chain_id, LinkSlot::Index(0)),
+			AccountLinkerError::LinkNotFound
+		);
+	});
+}
+
+#[test]
+fn test_link_doge() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([1u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = DogeVerifier::<Test, DogecoinParams>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[1u8; 32]).unwrap();
+		let addr = p2pkh_addr(0x1e, &secret);
+		let sig = sign_link_message(DOGECOIN_MAGIC, &secret, 31, &account, block_number);
+
+		let _ = Balances::deposit_creating(&account, 100);
+		events();
+
+		assert_ok!(AccountLinker::link(
+			Origin::signed(account.clone()),
+			chain_id,
+			account.clone(),
+			0,
+			addr.clone(),
+			block_number,
+			sig.clone()
+		));
+
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![addr.clone()]);
+		assert_eq!(AccountLinker::chain_owners(chain_id, &addr), vec![account.clone()]);
+		assert_eq!(AccountLinker::link_nonce(&account), 1);
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(
+			events(),
+			[
+				Event::pallet_balances(pallet_balances::Event::Reserved(account.clone(), 10)),
+				Event::account_linker(crate::Event::AddressLinked(account.clone(), chain_id, addr.clone(), 0, None)),
+			]
+		);
+
+		// The signature commits to the nonce, so it can't be replayed
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 1, addr, block_number, sig),
+			AccountLinkerError::UnexpectedAddress
+		);
+	});
+}
+
+#[test]
+fn test_link_doge_invalid_signature() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([2u8; 32]);
+		let other: AccountId32 = AccountId32::from([3u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = DogeVerifier::<Test, DogecoinParams>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[2u8; 32]).unwrap();
+		let addr = p2pkh_addr(0x1e, &secret);
+
+		// signed for another account
+		let sig = sign_link_message(DOGECOIN_MAGIC, &secret, 31, &other, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr.clone(), block_number, sig),
+			AccountLinkerError::UnexpectedAddress
+		);
+
+		// signed by another key
+		let secret = SecretKey::from_slice(&[3u8; 32]).unwrap();
+		let sig = sign_link_message(DOGECOIN_MAGIC, &secret, 31, &account, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 0, addr, block_number, sig),
+			AccountLinkerError::UnexpectedAddress
+		);
+	});
+}
+
+#[test]
+fn test_link_doge_expired() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([4u8; 32]);
+		let chain_id = DogeVerifier::<Test, DogecoinParams>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[4u8; 32]).unwrap();
+		let addr = p2pkh_addr(0x1e, &secret);
+		let sig = sign_link_message(DOGECOIN_MAGIC, &secret, 31, &account, 1);
+
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account, 0, addr, 1, sig),
+			AccountLinkerError::LinkRequestExpired
+		);
+	});
+}
+
+#[test]
+fn test_unlink_doge() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+		set_link_deposit(10);
+
+		let account: AccountId32 = AccountId32::from([5u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = DogeVerifier::<Test, DogecoinParams>::CHAIN_ID;
+
+		let _ = Balances::deposit_creating(&account, 100);
+
+		let mut linked = Vec::new();
+		for i in 0..2u8 {
+			let secret = SecretKey::from_slice(&[10 + i; 32]).unwrap();
+			let addr = p2pkh_addr(0x1e, &secret);
+			let sig = sign_link_message(DOGECOIN_MAGIC, &secret, 31, &account, block_number);
+			assert_ok!(AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), i as u32, addr.clone(), block_number, sig));
+			linked.push(addr);
+		}
+		assert_eq!(Balances::reserved_balance(&account), 20);
+		events();
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Address(linked[0].clone())));
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![linked[1].clone()]);
+		assert!(AccountLinker::chain_owners(chain_id, &linked[0]).is_empty());
+		assert_eq!(Balances::reserved_balance(&account), 10);
+		assert_eq!(
+			events(),
+			[
+				Event::pallet_balances(pallet_balances::Event::Unreserved(account.clone(), 10)),
+				Event::account_linker(crate::Event::AddressUnlinked(account.clone(), chain_id, linked[0].clone())),
+			]
+		);
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Index(0)));
+		assert!(AccountLinker::chain_addresses(chain_id, &account).is_empty());
+		assert_eq!(Balances::reserved_balance(&account), 0);
+		assert_eq!(Balances::free_balance(&account), 100);
+
+		assert_noop!(
+			AccountLinker::unlink(Origin::signed(account), chain_id, LinkSlot::Index(0)),
+			AccountLinkerError::LinkNotFound
+		);
+	});
+}
+
+#[test]
+fn test_link_ltc_address_types() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([1u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = LtcVerifier::<Test, LitecoinParams>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[1u8; 32]).unwrap();
+		// P2PKH `L...`, P2SH-P2WPKH `M...` and P2WPKH `ltc1...` addresses with their BIP-137 headers
+		let addrs = vec![
+			(p2pkh_addr(0x30, &secret), 31),
+			(p2sh_p2wpkh_addr(0x32, &secret), 35),
+			(p2wpkh_addr("ltc", &secret), 39),
+		];
+		assert!(addrs[0].0.starts_with(b"L"));
+		assert!(addrs[1].0.starts_with(b"M"));
+
+		for (i, (addr, header_base)) in addrs.iter().enumerate() {
+			let sig = sign_link_message(LITECOIN_MAGIC, &secret, *header_base, &account, block_number);
+			assert_ok!(AccountLinker::link(
+				Origin::signed(account.clone()),
+				chain_id,
+				account.clone(),
+				i as u32,
+				addr.clone(),
+				block_number,
+				sig
+			));
+			assert_eq!(AccountLinker::chain_owners(chain_id, addr), vec![account.clone()]);
+		}
+
+		let addrs: Vec<Vec<u8>> = addrs.into_iter().map(|(addr, _)| addr).collect();
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), addrs);
+		assert_eq!(AccountLinker::linked_accounts(&account).chains, vec![(chain_id, addrs.clone())]);
+		// Litecoin addresses are stored separately from btc addresses
+		assert!(AccountLinker::btc_addresses(&account).is_empty());
+		assert!(AccountLinker::chain_addresses(DogeVerifier::<Test, DogecoinParams>::CHAIN_ID, &account).is_empty());
+		assert_eq!(AccountLinker::link_nonce(&account), 3);
+		assert_eq!(
+			events(),
+			addrs.iter().enumerate().map(|(i, addr)|
+				Event::account_linker(crate::Event::AddressLinked(account.clone(), chain_id, addr.clone(), i as u32, None))
+			).collect::<Vec<_>>()
+		);
+	});
+}
+
+#[test]
+fn test_link_ltc_invalid() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([2u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = LtcVerifier::<Test, LitecoinParams>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[2u8; 32]).unwrap();
+		let addr = p2pkh_addr(0x30, &secret);
+
+		// signed as a Bitcoin message
+		let sig = sign_link_message(BITCOIN_MAGIC, &secret, 31, &account, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr, block_number, sig),
+			AccountLinkerError::UnexpectedAddress
+		);
+
+		// a btc address
+		let sig = sign_link_message(LITECOIN_MAGIC, &secret, 31, &account, block_number);
+		assert_noop!(
+			AccountLinker::link(
+				Origin::signed(account.clone()), chain_id, account.clone(), 0, p2pkh_addr(0x00, &secret), block_number, sig.clone()
+			),
+			AccountLinkerError::InvalidBTCAddress
+		);
+
+		// Taproot addresses are not linkable on Litecoin
+		assert_noop!(
+			AccountLinker::link(
+				Origin::signed(account.clone()),
+				chain_id,
+				account.clone(),
+				0,
+				b"ltc1pqqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0sts9tf8".to_vec(),
+				block_number,
+				sig),
+			AccountLinkerError::InvalidBTCAddress
+		);
+	});
+}
+
+#[test]
+fn test_link_doge_no_segwit() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([3u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = DogeVerifier::<Test, DogecoinParams>::CHAIN_ID;
+
+		let secret = SecretKey::from_slice(&[3u8; 32]).unwrap();
+		let addr = p2pkh_addr(0x1e, &secret);
+		assert!(addr.starts_with(b"D"));
+
+		let sig = sign_link_message(DOGECOIN_MAGIC, &secret, 31, &account, block_number);
+		assert_ok!(AccountLinker::link(
+			Origin::signed(account.clone()),
+			chain_id,
+			account.clone(),
+			0,
+			addr.clone(),
+			block_number,
+			sig
+		));
+
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![addr.clone()]);
+		assert_eq!(AccountLinker::chain_owners(chain_id, &addr), vec![account.clone()]);
+		assert_eq!(AccountLinker::linked_accounts(&account).chains, vec![(chain_id, vec![addr.clone()])]);
+		assert!(AccountLinker::chain_addresses(LtcVerifier::<Test, LitecoinParams>::CHAIN_ID, &account).is_empty());
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::AddressLinked(account.clone(), chain_id, addr, 0, None)),
+			]
+		);
+
+		// Dogecoin has no SegWit, so neither nested nor native SegWit addresses are linkable
+		let sig = sign_link_message(DOGECOIN_MAGIC, &secret, 35, &account, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 1, p2sh_p2wpkh_addr(0x16, &secret), block_number, sig),
+			AccountLinkerError::InvalidBTCAddress
+		);
+		let sig = sign_link_message(DOGECOIN_MAGIC, &secret, 39, &account, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 1, p2wpkh_addr("bc", &secret), block_number, sig),
+			AccountLinkerError::InvalidBTCAddress
+		);
+	});
+}
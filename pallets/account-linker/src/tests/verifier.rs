@@ -0,0 +1,232 @@
+use crate::{mock::*, ExternalAddress, LinkPolicy, LinkSlot, LinkVerifiers};
+
+use frame_support::{assert_ok, assert_noop};
+use sp_core::{ed25519, Pair};
+use sp_runtime::AccountId32;
+
+// The hex address of the key and its signature of `message`
+fn sign_message(pair: &ed25519::Pair, message: &[u8]) -> (Vec<u8>, Vec<u8>) {
+	(hex::encode(pair.public().0).into_bytes(), pair.sign(message).0.to_vec())
+}
+
+fn sign_link_message(pair: &ed25519::Pair, account: &AccountId32, block_number: u32) -> (Vec<u8>, Vec<u8>) {
+	sign_message(pair, &AccountLinker::link_message(account, block_number))
+}
+
+#[test]
+fn test_link_chain() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([1u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = HexEd25519Verifier::CHAIN_ID;
+
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let (addr, sig) = sign_link_message(&pair, &account, block_number);
+
+		assert_ok!(AccountLinker::link(
+			Origin::signed(account.clone()),
+			chain_id,
+			account.clone(),
+			0,
+			addr.clone(),
+			block_number,
+			sig.clone()
+		));
+
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![addr.clone()]);
+		assert_eq!(AccountLinker::chain_owners(chain_id, &addr), vec![account.clone()]);
+		assert_eq!(AccountLinker::owner_of(&ExternalAddress::Chain(chain_id, addr.clone())), vec![account.clone()]);
+		assert_eq!(AccountLinker::linked_accounts(&account).chains, vec![(chain_id, vec![addr.clone()])]);
+		assert_eq!(AccountLinker::link_nonce(&account), 1);
+		assert_eq!(link_changes(), vec![LinkChange::Linked(account.clone(), ExternalAddress::Chain(chain_id, addr.clone()))]);
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::AddressLinked(account.clone(), chain_id, addr.clone(), 0, None)),
+			]
+		);
+
+		// The signature commits to the nonce, so it can't be replayed
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 1, addr, block_number, sig),
+			INVALID_SIGNATURE
+		);
+	});
+}
+
+#[test]
+fn test_link_chain_by_chain_id() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([2u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = PrefixedHexEd25519Verifier::CHAIN_ID;
+
+		let pair = ed25519::Pair::from_seed(&[2u8; 32]);
+
+		// the wallets of the chain sign the prefixed link message
+		let (addr, sig) = sign_link_message(&pair, &account, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr.clone(), block_number, sig),
+			INVALID_SIGNATURE
+		);
+
+		let mut message = b"\x19Test Signed Message:\n".to_vec();
+		message.extend_from_slice(&AccountLinker::link_message(&account, block_number));
+		let (_, sig) = sign_message(&pair, &message);
+		assert_ok!(AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr.clone(), block_number, sig));
+
+		// the addresses of each chain are stored separately
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![addr.clone()]);
+		assert!(AccountLinker::chain_addresses(HexEd25519Verifier::CHAIN_ID, &account).is_empty());
+		assert!(AccountLinker::chain_owners(HexEd25519Verifier::CHAIN_ID, &addr).is_empty());
+
+		let (addr, sig) = sign_link_message(&pair, &account, block_number);
+		assert_ok!(AccountLinker::link(Origin::signed(account.clone()), HexEd25519Verifier::CHAIN_ID, account.clone(), 0, addr.clone(), block_number, sig));
+		assert_eq!(
+			AccountLinker::linked_accounts(&account).chains,
+			vec![(HexEd25519Verifier::CHAIN_ID, vec![addr.clone()]), (chain_id, vec![addr])]
+		);
+	});
+}
+
+#[test]
+fn test_link_chain_invalid() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([3u8; 32]);
+		let other: AccountId32 = AccountId32::from([4u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = HexEd25519Verifier::CHAIN_ID;
+
+		let pair = ed25519::Pair::from_seed(&[3u8; 32]);
+		let (addr, sig) = sign_link_message(&pair, &account, block_number);
+
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), 7, account.clone(), 0, addr.clone(), block_number, sig.clone()),
+			AccountLinkerError::UnknownChain
+		);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr.to_ascii_uppercase(), block_number, sig.clone()),
+			INVALID_ADDRESS
+		);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr.clone(), block_number, sig[..63].to_vec()),
+			INVALID_SIGNATURE
+		);
+
+		// signed by another key
+		let (other_addr, _) = sign_link_message(&ed25519::Pair::from_seed(&[4u8; 32]), &account, block_number);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, other_addr, block_number, sig.clone()),
+			INVALID_SIGNATURE
+		);
+
+		let (_, expired_sig) = sign_link_message(&pair, &account, 0);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), 0, addr.clone(), 0, expired_sig),
+			AccountLinkerError::LinkRequestExpired
+		);
+
+		set_link_policy(LinkPolicy::SelfOnly);
+		assert_noop!(
+			AccountLinker::link(Origin::signed(other), chain_id, account.clone(), 0, addr, block_number, sig),
+			AccountLinkerError::UnauthorizedOrigin
+		);
+	});
+}
+
+#[test]
+fn test_link_chain_policy() {
+	new_test_ext().execute_with(|| {
+
+		let account: AccountId32 = AccountId32::from([5u8; 32]);
+		let delegate: AccountId32 = AccountId32::from([6u8; 32]);
+		let other: AccountId32 = AccountId32::from([7u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = HexEd25519Verifier::CHAIN_ID;
+
+		add_link_delegate(account.clone(), delegate.clone());
+
+		let mut seed = 0u8;
+		let mut link = |origin: &AccountId32| {
+			seed += 1;
+			let (addr, sig) = sign_link_message(&ed25519::Pair::from_seed(&[seed; 32]), &account, block_number);
+			AccountLinker::link(Origin::signed(origin.clone()), chain_id, account.clone(), 0, addr, block_number, sig)
+		};
+
+		// Any origin can submit the link signature
+		assert_ok!(link(&other));
+
+		set_link_policy(LinkPolicy::SelfOnly);
+		assert_noop!(link(&other), AccountLinkerError::UnauthorizedOrigin);
+		assert_noop!(link(&delegate), AccountLinkerError::UnauthorizedOrigin);
+		assert_ok!(link(&account));
+
+		set_link_policy(LinkPolicy::SelfOrProxy);
+		assert_noop!(link(&other), AccountLinkerError::UnauthorizedOrigin);
+		assert_ok!(link(&delegate));
+		assert_ok!(link(&account));
+
+		assert_eq!(AccountLinker::link_nonce(&account), 4);
+	});
+}
+
+#[test]
+fn test_unlink_chain() {
+	new_test_ext().execute_with(|| {
+
+		run_to_block(1);
+
+		let account: AccountId32 = AccountId32::from([5u8; 32]);
+		let block_number: u32 = 99999;
+		let chain_id = HexEd25519Verifier::CHAIN_ID;
+
+		let mut linked = Vec::new();
+		for i in 0..2u8 {
+			let (addr, sig) = sign_link_message(&ed25519::Pair::from_seed(&[10 + i; 32]), &account, block_number);
+			assert_ok!(AccountLinker::link(Origin::signed(account.clone()), chain_id, account.clone(), i as u32, addr.clone(), block_number, sig));
+			linked.push(addr);
+		}
+		events();
+		link_changes();
+
+		// the slots of other chains are separate
+		assert_noop!(
+			AccountLinker::unlink(Origin::signed(account.clone()), PrefixedHexEd25519Verifier::CHAIN_ID, LinkSlot::Index(0)),
+			AccountLinkerError::LinkNotFound
+		);
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Address(linked[0].clone())));
+		assert_eq!(AccountLinker::chain_addresses(chain_id, &account), vec![linked[1].clone()]);
+		assert!(AccountLinker::chain_owners(chain_id, &linked[0]).is_empty());
+		assert_eq!(link_changes(), vec![LinkChange::Unlinked(account.clone(), ExternalAddress::Chain(chain_id, linked[0].clone()))]);
+
+		assert_ok!(AccountLinker::unlink(Origin::signed(account.clone()), chain_id, LinkSlot::Index(0)));
+		assert!(AccountLinker::linked_accounts(&account).chains.is_empty());
+		assert_eq!(
+			events(),
+			[
+				Event::account_linker(crate::Event::AddressUnlinked(account.clone(), chain_id, linked[0].clone())),
+				Event::account_linker(crate::Event::AddressUnlinked(account.clone(), chain_id, linked[1].clone())),
+			]
+		);
+	});
+}
+
+#[test]
+fn test_link_verifiers() {
+	type Verifiers = (HexEd25519Verifier, PrefixedHexEd25519Verifier);
+
+	assert_eq!(Verifiers::chain_ids(), vec![1000, 1001]);
+	assert_eq!(Verifiers::weight(1001), 2_000);
+	assert_eq!(Verifiers::weight(7), 0);
+	assert_eq!(Verifiers::validate_address(1000, b"00"), Some(Err(INVALID_ADDRESS)));
+	assert_eq!(Verifiers::validate_address(7, b"00"), None);
+
+	assert!(<() as LinkVerifiers>::chain_ids().is_empty());
+	assert_eq!(<() as LinkVerifiers>::recover_signer(1000, b"00", b"", b""), None);
+}
@@ -0,0 +1,151 @@
+//! Traits for other pallets to use the links of the account linker pallet.
+
+use crate::{ChainId, ExternalAddress};
+use frame_support::weights::Weight;
+use sp_runtime::DispatchError;
+use sp_std::prelude::*;
+
+/// Provides the addresses linked to an account
+pub trait LinkedAccountsProvider<AccountId> {
+	/// The eth addresses linked to `account`
+	fn eth_addresses(account: &AccountId) -> Vec<[u8; 20]>;
+
+	/// The btc addresses linked to `account`
+	fn btc_addresses(account: &AccountId) -> Vec<Vec<u8>>;
+
+	/// The polkadot accounts linked to `account`
+	fn polkadot_addresses(account: &AccountId) -> Vec<AccountId>;
+
+	/// The addresses of the chain of a link verifier linked to `account`
+	fn chain_addresses(chain_id: ChainId, account: &AccountId) -> Vec<Vec<u8>>;
+}
+
+/// Notified when an address is linked to or unlinked from an account
+pub trait OnLinkChanged<AccountId> {
+	/// `address` was linked to `account`
+	fn on_linked(_account: &AccountId, _address: &ExternalAddress<AccountId>) {}
+
+	/// `address` was unlinked from `account`, or replaced by another address
+	fn on_unlinked(_account: &AccountId, _address: &ExternalAddress<AccountId>) {}
+}
+
+impl<AccountId> OnLinkChanged<AccountId> for () {}
+
+/// Authorises delegates, e.g. proxies, to link addresses on behalf of an account
+pub trait LinkDelegates<AccountId> {
+	/// Whether `delegate` may link addresses to `account`
+	fn is_delegate(account: &AccountId, delegate: &AccountId) -> bool;
+}
+
+impl<AccountId> LinkDelegates<AccountId> for () {
+	fn is_delegate(_account: &AccountId, _delegate: &AccountId) -> bool {
+		false
+	}
+}
+
+/// Verifies that an address of a chain signed the link message, so that the chain can be linked
+/// with the generic `link` call by adding the verifier to `Config::LinkVerifiers`.
+/// Eth and btc are verified by the built-in `EthVerifier` and `BtcVerifier`.
+pub trait LinkVerifier {
+	/// The id of the chain in `link` calls, unique among the verifiers of a runtime
+	const CHAIN_ID: ChainId;
+
+	/// The error of a malformed address or an invalid signature
+	type Error: Into<DispatchError>;
+
+	/// Ensure `addr` is a well-formed address of the chain in its canonical encoding
+	fn validate_address(addr: &[u8]) -> Result<(), Self::Error>;
+
+	/// The message signed by the wallets of the chain for `link_message`, e.g. the link message
+	/// with the message prefix of the chain, or its hash
+	fn signed_message(link_message: &[u8]) -> Vec<u8>;
+
+	/// Verify `signature` of `message` and derive the address which signed it.
+	/// `addr_expected` is the validated address of the link, for signatures which don't commit
+	/// to the address type or which can't recover the public key.
+	fn recover_address(addr_expected: &[u8], message: &[u8], signature: &[u8]) -> Result<Vec<u8>, Self::Error>;
+
+	/// The weight of validating an address and recovering the signer of a signature, only.
+	/// The link message and the storage of the link are weighed by `WeightInfo::link`, so unlike
+	/// the weights of the dedicated link calls, e.g. `WeightInfo::link_eth`, it must not include them.
+	fn weight() -> Weight;
+
+	/// Validate `addr_expected` and derive the address which signed `link_message`
+	fn recover_signer(addr_expected: &[u8], link_message: &[u8], signature: &[u8]) -> Result<Vec<u8>, Self::Error> {
+		Self::validate_address(addr_expected)?;
+		Self::recover_address(addr_expected, &Self::signed_message(link_message), signature)
+	}
+}
+
+/// A tuple of `LinkVerifier`s, which verifies the links of each chain with the verifier of its id
+pub trait LinkVerifiers {
+	/// The chain ids of the verifiers
+	fn chain_ids() -> Vec<ChainId>;
+
+	/// Ensure `addr` is a well-formed address of the chain, `None` if no verifier has `chain_id`
+	fn validate_address(chain_id: ChainId, addr: &[u8]) -> Option<Result<(), DispatchError>>;
+
+	/// Validate `addr_expected` and derive the address which signed `link_message` with the verifier
+	/// of `chain_id`, `None` if no verifier has `chain_id`
+	fn recover_signer(
+		chain_id: ChainId,
+		addr_expected: &[u8],
+		link_message: &[u8],
+		signature: &[u8],
+	) -> Option<Result<Vec<u8>, DispatchError>>;
+
+	/// The weight of recovering the signer of a signature of the chain, 0 if no verifier has `chain_id`
+	fn weight(chain_id: ChainId) -> Weight;
+}
+
+macro_rules! impl_link_verifiers {
+	($($verifier:ident),*) => {
+		impl<$($verifier: LinkVerifier),*> LinkVerifiers for ($($verifier,)*) {
+			fn chain_ids() -> Vec<ChainId> {
+				vec![$($verifier::CHAIN_ID),*]
+			}
+
+			fn validate_address(_chain_id: ChainId, _addr: &[u8]) -> Option<Result<(), DispatchError>> {
+				$(
+					if _chain_id == $verifier::CHAIN_ID {
+						return Some($verifier::validate_address(_addr).map_err(Into::into));
+					}
+				)*
+				None
+			}
+
+			fn recover_signer(
+				_chain_id: ChainId,
+				_addr_expected: &[u8],
+				_link_message: &[u8],
+				_signature: &[u8],
+			) -> Option<Result<Vec<u8>, DispatchError>> {
+				$(
+					if _chain_id == $verifier::CHAIN_ID {
+						return Some($verifier::recover_signer(_addr_expected, _link_message, _signature).map_err(Into::into));
+					}
+				)*
+				None
+			}
+
+			fn weight(_chain_id: ChainId) -> Weight {
+				$(
+					if _chain_id == $verifier::CHAIN_ID {
+						return $verifier::weight();
+					}
+				)*
+				0
+			}
+		}
+	};
+}
+
+impl_link_verifiers!();
+impl_link_verifiers!(A);
+impl_link_verifiers!(A, B);
+impl_link_verifiers!(A, B, C);
+impl_link_verifiers!(A, B, C, D);
+impl_link_verifiers!(A, B, C, D, E);
+impl_link_verifiers!(A, B, C, D, E, F);
+impl_link_verifiers!(A, B, C, D, E, F, G);
+impl_link_verifiers!(A, B, C, D, E, F, G, H);
@@ -0,0 +1,75 @@
+use sp_std::prelude::*;
+use crate::btc::{legacy::hash160, witness::{Bech32, ToBase32, Variant}};
+
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Bech32 address with the human readable part `hrp` of a compressed secp256k1 public key
+pub fn cosmos_addr_from_pk(hrp: &[u8], pk: &[u8; 33]) -> Result<Vec<u8>, &'static str> {
+	hash160(pk).to_base32().encode(hrp.to_vec(), Variant::Bech32)
+}
+
+/// Returns the SHA-256 hash of the amino JSON sign doc of an ADR-036 `MsgSignData`,
+/// as signed by `signArbitrary` of Cosmos wallets, see
+/// https://github.com/cosmos/cosmos-sdk/blob/master/docs/architecture/adr-036-arbitrary-signature.md
+/// The fields of the sign doc are sorted and `signer` is a bech32 address, so it needs no escaping.
+pub fn adr036_sign_doc_hash(signer: &[u8], data: &[u8]) -> [u8; 32] {
+	let mut doc = b"{\"account_number\":\"0\",\"chain_id\":\"\",\"fee\":{\"amount\":[],\"gas\":\"0\"},\"memo\":\"\",\
+		\"msgs\":[{\"type\":\"sign/MsgSignData\",\"value\":{\"data\":\"".to_vec();
+	doc.extend_from_slice(&base64_encode(data));
+	doc.extend_from_slice(b"\",\"signer\":\"");
+	doc.extend_from_slice(signer);
+	doc.extend_from_slice(b"\"}}],\"sequence\":\"0\"}");
+	sp_io::hashing::sha2_256(&doc)
+}
+
+/// Standard base64 encoding with padding, see https://tools.ietf.org/html/rfc4648#section-4
+fn base64_encode(data: &[u8]) -> Vec<u8> {
+	let mut res = Vec::with_capacity((data.len() + 2) / 3 * 4);
+	for chunk in data.chunks(3) {
+		let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+		let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+		for i in 0..4 {
+			if i <= chunk.len() {
+				res.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize]);
+			} else {
+				res.push(b'=');
+			}
+		}
+	}
+	res
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn correct_base64() {
+		// test vectors of RFC 4648
+		let vectors: [(&str, &str); 7] = [
+			("", ""),
+			("f", "Zg=="),
+			("fo", "Zm8="),
+			("foo", "Zm9v"),
+			("foob", "Zm9vYg=="),
+			("fooba", "Zm9vYmE="),
+			("foobar", "Zm9vYmFy"),
+		];
+		for (data, encoded) in vectors.iter() {
+			assert_eq!(base64_encode(data.as_bytes()), encoded.as_bytes().to_vec());
+		}
+		assert_eq!(base64_encode(&[0xfb, 0xff]), b"+/8=".to_vec());
+	}
+
+	#[test]
+	fn correct_sign_doc() {
+		let signer = "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu";
+		let doc = format!(
+			"{{\"account_number\":\"0\",\"chain_id\":\"\",\"fee\":{{\"amount\":[],\"gas\":\"0\"}},\"memo\":\"\",\
+			\"msgs\":[{{\"type\":\"sign/MsgSignData\",\"value\":{{\"data\":\"{}\",\"signer\":\"{}\"}}}}],\"sequence\":\"0\"}}",
+			"Zm9vYmFy",
+			signer,
+		);
+		assert_eq!(adr036_sign_doc_hash(signer.as_bytes(), b"foobar"), sp_io::hashing::sha2_256(doc.as_bytes()));
+	}
+}
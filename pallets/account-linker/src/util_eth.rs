@@ -10,42 +10,124 @@ pub fn addr_from_sig(msg: [u8; 32], sig: [u8; 65]) -> Result<[u8; 20], sp_io::Ec
 	Ok(addr)
 }
 
+/// Half of the order of secp256k1, the largest `s` of a canonical (low-s) signature
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+	0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+	0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// Recovery id of the `v` of a signature, which is either the raw recovery id (0/1),
+/// 27/28 as returned by eth_sign, or `chain_id * 2 + 35/36` as defined by EIP-155.
+/// The chain id `(v - 35) / 2` of an EIP-155 `v` isn't checked: the signed link message isn't a
+/// transaction of any eth chain and already commits to the genesis hash of this chain.
+pub fn recovery_id(v: u64) -> Option<u8> {
+	match v {
+		0 | 1 => Some(v as u8),
+		27 | 28 => Some((v - 27) as u8),
+		35..=u64::MAX => Some(((v - 35) % 2) as u8),
+		_ => None,
+	}
+}
+
+/// Split the `yParityAndS` half of an EIP-2098 compact signature into `s` and the recovery id
+pub fn split_compact(vs: [u8; 32]) -> ([u8; 32], u8) {
+	let mut s = vs;
+	s[0] &= 0x7f;
+	(s, vs[0] >> 7)
+}
+
+/// Whether `s` is in the upper half of the curve order, i.e. the signature is malleable
+pub fn is_high_s(s: &[u8; 32]) -> bool {
+	*s > SECP256K1_HALF_ORDER
+}
+
 /// Returns a eth_sign-compatible hash of data to sign.
 /// The data is prefixed with special message to prevent
 /// malicious DApps from using the function to sign forged transactions.
-pub fn eth_data_hash(mut data: Vec<u8>) -> Result<[u8; 32], &'static str> {
-	const MSG_LEN: usize = 51;
-	if data.len() != MSG_LEN {
-		log::error!("Ethereum message has an unexpected length {} !!! Expected is {}.", data.len(), MSG_LEN);
-		return Err("Unexpected ethereum message length!");
-	}
-	let mut length_bytes = usize_to_u8_array(data.len())?;
-	let mut eth_data = b"\x19Ethereum Signed Message:\n".encode();
-	eth_data.append(&mut length_bytes);
-	eth_data.append(&mut data);
-	Ok(sp_io::hashing::keccak_256(&eth_data))
+pub fn eth_data_hash(data: Vec<u8>) -> [u8; 32] {
+	prefixed_data_hash(b"\x19Ethereum Signed Message:\n", data)
+}
+
+/// Keccak hash of `data` prefixed with `prefix` and the decimal length of `data`,
+/// as signed by eth_sign and the personal_sign variants of other keccak-based chains
+pub fn prefixed_data_hash(prefix: &[u8], mut data: Vec<u8>) -> [u8; 32] {
+	let mut length_bytes = usize_to_u8_array(data.len());
+	let mut prefixed_data = prefix.to_vec();
+	prefixed_data.append(&mut length_bytes);
+	prefixed_data.append(&mut data);
+	sp_io::hashing::keccak_256(&prefixed_data)
+}
+
+/// EIP-712 type of the link message, see https://eips.ethereum.org/EIPS/eip-712
+pub const LINK_TYPE: &[u8] = b"LinkLitentry(bytes account,uint256 expiringBlock,uint256 nonce)";
+pub const DOMAIN_TYPE: &[u8] = b"EIP712Domain(string name,string version,bytes32 salt)";
+pub const DOMAIN_NAME: &[u8] = b"Litentry";
+pub const DOMAIN_VERSION: &[u8] = b"1";
+
+/// hashStruct of EIP-712, `fields` are the encoded members of the struct in their declared order
+pub fn hash_struct(type_str: &[u8], fields: &[[u8; 32]]) -> [u8; 32] {
+	let mut data = sp_io::hashing::keccak_256(type_str).to_vec();
+	for field in fields {
+		data.extend_from_slice(field);
+	}
+	sp_io::hashing::keccak_256(&data)
+}
+
+/// Returns the EIP-712 hash of typed data to sign, from its domain separator and `hashStruct(message)`
+pub fn typed_data_hash(domain_separator: &[u8; 32], struct_hash: &[u8; 32]) -> [u8; 32] {
+	let mut data = b"\x19\x01".to_vec();
+	data.extend_from_slice(domain_separator);
+	data.extend_from_slice(struct_hash);
+	sp_io::hashing::keccak_256(&data)
+}
+
+/// Domain separator of the link messages, `salt` separates the chains the pallet runs on
+pub fn link_domain_separator(salt: &[u8; 32]) -> [u8; 32] {
+	hash_struct(DOMAIN_TYPE, &[
+		sp_io::hashing::keccak_256(DOMAIN_NAME),
+		sp_io::hashing::keccak_256(DOMAIN_VERSION),
+		*salt,
+	])
+}
+
+/// Returns the EIP-712 hash of a `LinkLitentry` message.
+/// `expiring_block` is the SCALE encoding of an unsigned integer, i.e. little endian.
+pub fn link_typed_data_hash(salt: &[u8; 32], account: &[u8], expiring_block: &[u8], nonce: u32) -> Result<[u8; 32], &'static str> {
+	let struct_hash = hash_struct(LINK_TYPE, &[
+		sp_io::hashing::keccak_256(account),
+		uint256_from_le(expiring_block)?,
+		uint256_from_le(&nonce.encode())?,
+	]);
+	Ok(typed_data_hash(&link_domain_separator(salt), &struct_hash))
+}
+
+/// Big endian uint256 of a little endian unsigned integer
+fn uint256_from_le(le: &[u8]) -> Result<[u8; 32], &'static str> {
+	if le.len() > 32 {
+		return Err("Unexpected uint256 length!");
+	}
+	let mut res = [0u8; 32];
+	for (i, b) in le.iter().enumerate() {
+		res[31 - i] = *b;
+	}
+	Ok(res)
 }
 
 /// Convert a usize type to a u8 array.
 /// The input is first converted as a string with decimal presentation,
 /// and then this string is converted to a byte array with UTF8 encoding.
-/// To avoid unnecessary complexity, the current function supports up to
-/// 2 digits unsigned decimal (range 0 - 99)
-fn usize_to_u8_array(length: usize) -> Result<Vec<u8>, &'static str> {
-	if length >= 100 {
-		Err("Unexpected ethereum message length!")
-	} else {
-		let digits = b"0123456789".encode();
-		let tens = length / 10;
-		let ones = length % 10;
-
-		let mut  vec_res: Vec<u8> = Vec::new();
-		if tens != 0 {
-			vec_res.push(digits[tens]);
+fn usize_to_u8_array(length: usize) -> Vec<u8> {
+	let mut vec_res: Vec<u8> = Vec::new();
+	let mut length = length;
+	loop {
+		vec_res.push(b'0' + (length % 10) as u8);
+		length /= 10;
+		if length == 0 {
+			break;
 		}
-		vec_res.push(digits[ones]);
-		Ok(vec_res)
 	}
+	vec_res.reverse();
+	vec_res
 }
 
 #[cfg(test)]
@@ -115,23 +197,112 @@ mod tests {
 	}
 
 	#[test]
-	fn msg_with_unexpected_length() {
-		let msg = b"Link Litentry: 0123456789abcdef0123456789abcdef999".encode();
-		assert_eq!(Err("Unexpected ethereum message length!"), eth_data_hash(msg));
+	fn normalised_recovery_id() {
+		assert_eq!(recovery_id(0), Some(0));
+		assert_eq!(recovery_id(1), Some(1));
+		assert_eq!(recovery_id(27), Some(0));
+		assert_eq!(recovery_id(28), Some(1));
+		// EIP-155 on mainnet (chain id 1) and on chain id 56
+		assert_eq!(recovery_id(37), Some(0));
+		assert_eq!(recovery_id(38), Some(1));
+		assert_eq!(recovery_id(147), Some(0));
+		assert_eq!(recovery_id(148), Some(1));
+		// chain ids above 110 don't fit a u8 `v`, e.g. 137
+		assert_eq!(recovery_id(2 * 137 + 35), Some(0));
+		assert_eq!(recovery_id(2 * 137 + 36), Some(1));
+		assert_eq!(recovery_id(u64::MAX), Some(0));
+		for v in [2, 4, 26, 29, 34].iter() {
+			assert_eq!(recovery_id(*v), None);
+		}
+	}
+
+	#[test]
+	fn correct_split_compact() {
+		let mut s_expected = [0x13u8; 32];
+		s_expected[31] = 0x76;
+
+		let (s, recovery_id) = split_compact(s_expected);
+		assert_eq!(s, s_expected);
+		assert_eq!(recovery_id, 0);
+
+		// The recovery id is the highest bit of `yParityAndS`
+		let mut vs = s_expected;
+		vs[0] |= 0x80;
+		let (s, recovery_id) = split_compact(vs);
+		assert_eq!(s, s_expected);
+		assert_eq!(recovery_id, 1);
 	}
 
 	#[test]
-	fn msg_with_expected_length() {
-		let msg = b"Link Litentry: 0123456789abcdef0123456789abcdef9999".encode();
-		let res = eth_data_hash(msg.clone()).ok().unwrap();
-		assert_eq!(eth_data_hash_test_helper(msg), res);
+	fn high_s() {
+		assert!(!is_high_s(&[0u8; 32]));
+		assert!(!is_high_s(&SECP256K1_HALF_ORDER));
+
+		let mut s = SECP256K1_HALF_ORDER;
+		s[31] += 1;
+		assert!(is_high_s(&s));
+		assert!(is_high_s(&[0xff; 32]));
+	}
+
+	#[test]
+	fn msg_with_any_length() {
+		for len in [0, 7, 51, 88, 92, 100, 1234].iter() {
+			let msg = vec![b'a'; *len];
+			assert_eq!(eth_data_hash_test_helper(msg.clone()), eth_data_hash(msg));
+		}
+	}
+
+	fn string(s: &str) -> [u8; 32] {
+		sp_io::hashing::keccak_256(s.as_bytes())
+	}
+
+	fn address(s: &str) -> [u8; 32] {
+		let mut res = [0u8; 32];
+		res[12..].copy_from_slice(&decode(s).unwrap());
+		res
+	}
+
+	// The `Mail` example of EIP-712
+	#[test]
+	fn correct_typed_data_hash() {
+		let domain_separator = hash_struct(b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)", &[
+			string("Ether Mail"),
+			string("1"),
+			uint256_from_le(&[1]).unwrap(),
+			address("CcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"),
+		]);
+		assert_eq!(domain_separator.to_vec(), decode("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f").unwrap());
+
+		let person_type = "Person(string name,address wallet)";
+		let from = hash_struct(person_type.as_bytes(), &[string("Cow"), address("CD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826")]);
+		let to = hash_struct(person_type.as_bytes(), &[string("Bob"), address("bBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB")]);
+		let mail = hash_struct(format!("Mail(Person from,Person to,string contents){}", person_type).as_bytes(), &[
+			from,
+			to,
+			string("Hello, Bob!"),
+		]);
+		assert_eq!(mail.to_vec(), decode("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e").unwrap());
+
+		let hash = typed_data_hash(&domain_separator, &mail);
+		assert_eq!(hash.to_vec(), decode("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2").unwrap());
+	}
+
+	#[test]
+	fn correct_uint256_from_le() {
+		let mut expected = [0u8; 32];
+		expected[29] = 1;
+		expected[30] = 0x86;
+		expected[31] = 0x9f;
+		assert_eq!(uint256_from_le(&99999u32.encode()), Ok(expected));
+		assert_eq!(uint256_from_le(&99999u64.encode()), Ok(expected));
+		assert_eq!(uint256_from_le(&[1u8; 33]), Err("Unexpected uint256 length!"));
 	}
 
 	// Test input with more than 2 digits
 	#[test]
-	fn usize_to_u8_array_input_too_large() {
+	fn usize_to_u8_array_input_three_digits() {
 		let len: usize = 105;
-		assert_eq!(Err("Unexpected ethereum message length!"), usize_to_u8_array(len))
+		assert_eq!(b"105".to_vec(), usize_to_u8_array(len))
 	}
 
 	// Test inputs with one and two digits respectively
@@ -141,12 +312,19 @@ mod tests {
 	#[test]
 	fn usize_to_u8_array_input_one_digit() {
 		let len: usize = 4;
-		assert_eq!(Ok(vec![52]), usize_to_u8_array(len))
+		assert_eq!(vec![52], usize_to_u8_array(len))
 	}
 
 	#[test]
 	fn usize_to_u8_array_input_two_digits() {
 		let len: usize = 40;
-		assert_eq!(Ok(vec![52, 48]), usize_to_u8_array(len))
+		assert_eq!(vec![52, 48], usize_to_u8_array(len))
+	}
+
+	// Test input zero
+	#[test]
+	fn usize_to_u8_array_input_zero() {
+		let len: usize = 0;
+		assert_eq!(vec![48], usize_to_u8_array(len))
 	}
 }
@@ -0,0 +1,55 @@
+use sp_std::prelude::*;
+use crate::btc::{base58::ToBase58, legacy};
+use crate::util_eth;
+
+/// Version byte of Tron addresses, their base58check encoding starts with `T`
+pub const TRON_ADDR_VERSION: u8 = 0x41;
+
+/// Returns the TIP-191 hash of data signed by Tron wallets with `signMessageV2`, see
+/// https://github.com/tronprotocol/tips/blob/master/tip-191.md
+pub fn tron_data_hash(data: Vec<u8>) -> [u8; 32] {
+	util_eth::prefixed_data_hash(b"\x19TRON Signed Message:\n", data)
+}
+
+/// Base58check Tron address of the keccak-derived address `addr`, the same as the eth address of the key
+pub fn tron_addr(addr: &[u8; 20]) -> Vec<u8> {
+	legacy::addr_from_hash(TRON_ADDR_VERSION, addr).to_base58()
+}
+
+/// The keccak-derived address of a base58check Tron address, `None` if the address is malformed
+pub fn tron_addr_hash(addr: &[u8]) -> Option<[u8; 20]> {
+	match legacy::addr_hash(addr) {
+		Some((TRON_ADDR_VERSION, hash)) => Some(hash),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hex::decode;
+
+	#[test]
+	fn correct_tron_addr() {
+		let mut hash = [0u8; 20];
+		hash.copy_from_slice(&decode("5a523b449890854c8fc460ab602df9f31fe4293f").unwrap());
+		let addr = b"TJCnKsPa7y5okkXvQAidZBzqx3QyQ6sxMW".to_vec();
+
+		assert_eq!(tron_addr(&hash), addr);
+		assert_eq!(tron_addr_hash(&addr), Some(hash));
+
+		// invalid checksum
+		assert_eq!(tron_addr_hash(b"TJCnKsPa7y5okkXvQAidZBzqx3QyQ6sxMX"), None);
+		// a btc address has another version byte
+		assert_eq!(tron_addr_hash(b"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"), None);
+	}
+
+	#[test]
+	fn correct_tron_data_hash() {
+		let msg = b"Link Litentry".to_vec();
+		let mut data = format!("\x19TRON Signed Message:\n{}", msg.len()).into_bytes();
+		data.extend_from_slice(&msg);
+
+		assert_eq!(tron_data_hash(msg), sp_io::hashing::keccak_256(&data));
+	}
+}
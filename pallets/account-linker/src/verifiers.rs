@@ -0,0 +1,313 @@
+//! The verifiers of the chains linked with `link`, identified by the SLIP-44 coin type of the chain.
+//! `EthVerifier` and `BtcVerifier` verify the links of `link_eth` and `link_btc`, which are always
+//! linkable, the others are added to `Config::LinkVerifiers` by the runtimes linking their chain.
+//! Recoverable secp256k1 signatures are `r ++ s ++ v`, as the arguments of `link_eth` and `link_btc`.
+
+use crate::{btc, util_cosmos, util_eth, util_tron, ChainId, LinkVerifier, UtxoChainParams};
+use crate::pallet::{Config, Error, Pallet};
+use crate::weights::WeightInfo;
+use btc::base58::{FromBase58, ToBase58};
+use btc::witness::FromBase32;
+use frame_support::{ensure, traits::Get, weights::Weight};
+use sp_io::crypto::secp256k1_ecdsa_recover_compressed;
+use sp_runtime::app_crypto::ed25519;
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// A signature as `r ++ s ++ v`
+pub(crate) fn join_signature(r: [u8; 32], s: [u8; 32], v: u8) -> Vec<u8> {
+	let mut sig = r.to_vec();
+	sig.extend_from_slice(&s);
+	sig.push(v);
+	sig
+}
+
+/// Split a signature encoded by `join_signature` into its `r`, `s` and `v`
+fn split_signature<T: Config>(sig: &[u8]) -> Result<([u8; 32], [u8; 32], u8), Error<T>> {
+	ensure!(sig.len() == 65, Error::<T>::InvalidSignatureLength);
+
+	let (r, s) = split_halves(&sig[..64]);
+	Ok((r, s, sig[64]))
+}
+
+/// The two halves of a 64-byte signature
+fn split_halves(sig: &[u8]) -> ([u8; 32], [u8; 32]) {
+	let mut r = [0u8; 32];
+	let mut s = [0u8; 32];
+	r.copy_from_slice(&sig[..32]);
+	s.copy_from_slice(&sig[32..64]);
+	(r, s)
+}
+
+/// The weight of recovering the signer of the UTXO chain of `params`, that of a BIP-322 signature
+/// if Taproot addresses are linkable since it is verified in the runtime
+fn utxo_weight<T: Config>(params: &UtxoChainParams) -> Weight {
+	if params.taproot {
+		T::WeightInfo::recover_bip322()
+	} else {
+		T::WeightInfo::recover_bip137()
+	}
+}
+
+/// Verifies eth_sign signatures of 20-byte eth addresses, the links of `link_eth`.
+/// Signatures are `r ++ s ++ v` with `v` the raw recovery id, 27/28 or an EIP-155 value of a byte,
+/// or EIP-2098 compact signatures `r ++ vs` as passed to `link_eth_compact`.
+pub struct EthVerifier<T>(PhantomData<T>);
+
+impl<T: Config> LinkVerifier for EthVerifier<T> {
+	// SLIP-44 coin type of Ether
+	const CHAIN_ID: ChainId = 60;
+
+	type Error = Error<T>;
+
+	fn validate_address(addr: &[u8]) -> Result<(), Error<T>> {
+		ensure!(addr.len() == 20, Error::<T>::InvalidEthAddress);
+		Ok(())
+	}
+
+	fn signed_message(link_message: &[u8]) -> Vec<u8> {
+		util_eth::eth_data_hash(link_message.to_vec()).to_vec()
+	}
+
+	fn recover_address(_addr_expected: &[u8], message: &[u8], signature: &[u8]) -> Result<Vec<u8>, Error<T>> {
+		ensure!(message.len() == 32, Error::<T>::EcdsaRecoverFailure);
+		let (r, s, v) = match signature.len() {
+			64 => {
+				let (r, vs) = split_halves(signature);
+				let (s, v) = util_eth::split_compact(vs);
+				(r, s, v)
+			},
+			_ => split_signature::<T>(signature)?,
+		};
+
+		let mut hash = [0u8; 32];
+		hash.copy_from_slice(message);
+		Ok(Pallet::<T>::eth_addr_from_sig(hash, r, s, v.into())?.to_vec())
+	}
+
+	fn weight() -> Weight {
+		T::WeightInfo::recover_eth()
+	}
+}
+
+/// Verifies BIP-137 and BIP-322 signatures of the btc addresses of `T::BitcoinNetwork`, the links of `link_btc`.
+/// `v` is the BIP-137 header of the signature or the BIP-322 sighash type.
+pub struct BtcVerifier<T>(PhantomData<T>);
+
+impl<T: Config> LinkVerifier for BtcVerifier<T> {
+	// SLIP-44 coin type of Bitcoin
+	const CHAIN_ID: ChainId = 0;
+
+	type Error = Error<T>;
+
+	fn validate_address(addr: &[u8]) -> Result<(), Error<T>> {
+		Pallet::<T>::validate_utxo_addr(&T::BitcoinNetwork::get().params(), addr)
+	}
+
+	// BIP-137 and BIP-322 hash the message differently, so it is hashed by `recover_address`
+	// for the type of the address
+	fn signed_message(link_message: &[u8]) -> Vec<u8> {
+		link_message.to_vec()
+	}
+
+	fn recover_address(addr_expected: &[u8], message: &[u8], signature: &[u8]) -> Result<Vec<u8>, Error<T>> {
+		let (r, s, v) = split_signature::<T>(signature)?;
+		Pallet::<T>::utxo_addr_from_signature(&T::BitcoinNetwork::get().params(), addr_expected, message, r, s, v)
+	}
+
+	fn weight() -> Weight {
+		utxo_weight::<T>(&T::BitcoinNetwork::get().params())
+	}
+}
+
+/// Verifies BIP-137 signatures of the Litecoin signed message of the link message, see `BtcVerifier`.
+/// Addresses must belong to the chain described by `P`, e.g. `UtxoChainParams::LITECOIN`.
+pub struct LtcVerifier<T, P>(PhantomData<(T, P)>);
+
+impl<T: Config, P: Get<UtxoChainParams>> LinkVerifier for LtcVerifier<T, P> {
+	// SLIP-44 coin type of Litecoin
+	const CHAIN_ID: ChainId = 2;
+
+	type Error = Error<T>;
+
+	fn validate_address(addr: &[u8]) -> Result<(), Error<T>> {
+		Pallet::<T>::validate_utxo_addr(&P::get(), addr)
+	}
+
+	fn signed_message(link_message: &[u8]) -> Vec<u8> {
+		link_message.to_vec()
+	}
+
+	fn recover_address(addr_expected: &[u8], message: &[u8], signature: &[u8]) -> Result<Vec<u8>, Error<T>> {
+		let (r, s, v) = split_signature::<T>(signature)?;
+		Pallet::<T>::utxo_addr_from_signature(&P::get(), addr_expected, message, r, s, v)
+	}
+
+	fn weight() -> Weight {
+		utxo_weight::<T>(&P::get())
+	}
+}
+
+/// Verifies BIP-137 signatures of the Dogecoin signed message of the link message, see `BtcVerifier`.
+/// Addresses must belong to the chain described by `P`, e.g. `UtxoChainParams::DOGECOIN`.
+pub struct DogeVerifier<T, P>(PhantomData<(T, P)>);
+
+impl<T: Config, P: Get<UtxoChainParams>> LinkVerifier for DogeVerifier<T, P> {
+	// SLIP-44 coin type of Dogecoin
+	const CHAIN_ID: ChainId = 3;
+
+	type Error = Error<T>;
+
+	fn validate_address(addr: &[u8]) -> Result<(), Error<T>> {
+		Pallet::<T>::validate_utxo_addr(&P::get(), addr)
+	}
+
+	fn signed_message(link_message: &[u8]) -> Vec<u8> {
+		link_message.to_vec()
+	}
+
+	fn recover_address(addr_expected: &[u8], message: &[u8], signature: &[u8]) -> Result<Vec<u8>, Error<T>> {
+		let (r, s, v) = split_signature::<T>(signature)?;
+		Pallet::<T>::utxo_addr_from_signature(&P::get(), addr_expected, message, r, s, v)
+	}
+
+	fn weight() -> Weight {
+		utxo_weight::<T>(&P::get())
+	}
+}
+
+/// Decode a Solana address, which must be the canonical base58 encoding of a 32-byte public key
+fn sol_public_key<T: Config>(addr: &[u8]) -> Result<[u8; 32], Error<T>> {
+	let decoded = addr.from_base58().ok_or(Error::<T>::InvalidSolAddress)?;
+	ensure!(decoded.len() == 32 && decoded.to_base58() == addr, Error::<T>::InvalidSolAddress);
+
+	let mut pk = [0u8; 32];
+	pk.copy_from_slice(&decoded);
+	Ok(pk)
+}
+
+/// Verifies ed25519 signatures of the link message, as signed by the `signMessage` method of Solana
+/// wallets. Addresses are base58 encoded public keys and signatures are 64 bytes.
+pub struct SolVerifier<T>(PhantomData<T>);
+
+impl<T: Config> LinkVerifier for SolVerifier<T> {
+	// SLIP-44 coin type of Solana
+	const CHAIN_ID: ChainId = 501;
+
+	type Error = Error<T>;
+
+	fn validate_address(addr: &[u8]) -> Result<(), Error<T>> {
+		sol_public_key::<T>(addr)?;
+		Ok(())
+	}
+
+	fn signed_message(link_message: &[u8]) -> Vec<u8> {
+		link_message.to_vec()
+	}
+
+	// ed25519 signatures don't recover the public key, so the verified address is `addr_expected`
+	fn recover_address(addr_expected: &[u8], message: &[u8], signature: &[u8]) -> Result<Vec<u8>, Error<T>> {
+		ensure!(signature.len() == 64, Error::<T>::InvalidSignatureLength);
+		let pk = sol_public_key::<T>(addr_expected)?;
+
+		let mut sig = [0u8; 64];
+		sig.copy_from_slice(signature);
+		ensure!(sp_io::crypto::ed25519_verify(&ed25519::Signature::from_raw(sig), message, &ed25519::Public::from_raw(pk)),
+			Error::<T>::InvalidSolSignature);
+
+		Ok(addr_expected.to_vec())
+	}
+
+	fn weight() -> Weight {
+		T::WeightInfo::recover_sol()
+	}
+}
+
+/// Verifies ADR-036 `signArbitrary` signatures of the link message by lowercase bech32 Cosmos addresses
+/// of 20 bytes with the hrp `Hrp`, e.g. `cosmos` or `osmo`. Signatures are the 64-byte `r ++ s` of the
+/// secp256k1 signature of the amino JSON sign doc.
+pub struct CosmosVerifier<T, Hrp>(PhantomData<(T, Hrp)>);
+
+impl<T: Config, Hrp: Get<&'static [u8]>> LinkVerifier for CosmosVerifier<T, Hrp> {
+	// SLIP-44 coin type of the Cosmos Hub
+	const CHAIN_ID: ChainId = 118;
+
+	type Error = Error<T>;
+
+	fn validate_address(addr: &[u8]) -> Result<(), Error<T>> {
+		let valid = match btc::witness::decode(addr) {
+			Ok((hrp, data, btc::witness::Variant::Bech32)) => hrp == Hrp::get()
+				&& !addr.iter().any(|c| c.is_ascii_uppercase())
+				&& data.from_base32().map_or(false, |program| program.len() == 20),
+			_ => false,
+		};
+		ensure!(valid, Error::<T>::InvalidCosmosAddress);
+		Ok(())
+	}
+
+	// the sign doc contains the signer, so it is hashed by `recover_address`
+	fn signed_message(link_message: &[u8]) -> Vec<u8> {
+		link_message.to_vec()
+	}
+
+	// ADR-036 signatures have no recovery id, so the public key is recovered with both ids.
+	// As in the Cosmos SDK, high-`s` signatures are rejected.
+	fn recover_address(addr_expected: &[u8], message: &[u8], signature: &[u8]) -> Result<Vec<u8>, Error<T>> {
+		ensure!(signature.len() == 64, Error::<T>::InvalidSignatureLength);
+		let (r, s) = split_halves(signature);
+		ensure!(!util_eth::is_high_s(&s), Error::<T>::InvalidCosmosSignature);
+
+		let hash = util_cosmos::adr036_sign_doc_hash(addr_expected, message);
+		let mut sig = [0u8; 65];
+		sig[..32].copy_from_slice(&r);
+		sig[32..64].copy_from_slice(&s);
+
+		for recovery_id in 0..2 {
+			sig[64] = recovery_id;
+			if let Ok(pk) = secp256k1_ecdsa_recover_compressed(&sig, &hash) {
+				let addr = util_cosmos::cosmos_addr_from_pk(Hrp::get(), &pk)
+					.map_err(|_| Error::<T>::InvalidCosmosAddress)?;
+				if addr == addr_expected {
+					return Ok(addr);
+				}
+			}
+		}
+		Err(Error::<T>::InvalidCosmosSignature)
+	}
+
+	fn weight() -> Weight {
+		T::WeightInfo::recover_cosmos()
+	}
+}
+
+/// Verifies TIP-191 signatures of the link message, as signed by the `signMessageV2` method of Tron
+/// wallets. Addresses are base58check encoded and `v` is read like the eth `v` of `EthVerifier`.
+pub struct TronVerifier<T>(PhantomData<T>);
+
+impl<T: Config> LinkVerifier for TronVerifier<T> {
+	// SLIP-44 coin type of Tron
+	const CHAIN_ID: ChainId = 195;
+
+	type Error = Error<T>;
+
+	fn validate_address(addr: &[u8]) -> Result<(), Error<T>> {
+		util_tron::tron_addr_hash(addr).ok_or(Error::<T>::InvalidTronAddress)?;
+		Ok(())
+	}
+
+	fn signed_message(link_message: &[u8]) -> Vec<u8> {
+		util_tron::tron_data_hash(link_message.to_vec()).to_vec()
+	}
+
+	fn recover_address(_addr_expected: &[u8], message: &[u8], signature: &[u8]) -> Result<Vec<u8>, Error<T>> {
+		ensure!(message.len() == 32, Error::<T>::EcdsaRecoverFailure);
+		let (r, s, v) = split_signature::<T>(signature)?;
+
+		let mut hash = [0u8; 32];
+		hash.copy_from_slice(message);
+		Ok(util_tron::tron_addr(&Pallet::<T>::eth_addr_from_sig(hash, r, s, v.into())?))
+	}
+
+	fn weight() -> Weight {
+		T::WeightInfo::recover_tron()
+	}
+}
@@ -15,26 +15,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Autogenerated weights for pallet_account_linker
+//! Weights for pallet_account_linker
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 3.0.0
-//! DATE: 2021-02-25, STEPS: [20, ], REPEAT: 50, LOW RANGE: [], HIGH RANGE: []
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 128
-
-// Executed Command:
-// target/release/litentry-node
-// benchmark
-// --chain=dev
-// --execution=wasm
-// --wasm-execution=compiled
-// --pallet=pallet_account_linker
-// --extrinsic=*
-// --heap-pages=4096
-// --steps=20
-// --repeat=50
-// --output=./pallets/account-linker/src/weights.rs
-// --template=./.maintain/frame-weight-template.hbs
-
+//! These weights are not the output of the benchmark CLI, this repository has no node to run
+//! `benchmarking.rs` with. The base weights of `link_eth`, `link_btc`, `link_polkadot` and
+//! `accept_polkadot` come from a benchmark run of their first versions on 2021-02-25, and those
+//! of the calls added since are estimated from the call verifying its signature the same way,
+//! e.g. `link_btc_taproot` verifies a Schnorr signature in the runtime instead of a host function.
+//! The weights of the link verifiers, e.g. `recover_sol`, are those of the link calls of their chain
+//! before they were linked with `link`, less the weight of `link`.
+//! The per-link factors and the storage reads and writes are counted from the implementation.
+//!
+//! Regenerate them with the `benchmark` subcommand of a node built with `runtime-benchmarks`:
+//! `benchmark --chain=dev --execution=wasm --wasm-execution=compiled --pallet=pallet_account_linker
+//! --extrinsic=* --steps=20 --repeat=50 --output=./pallets/account-linker/src/weights.rs`
 
 #![allow(unused_parens)]
 #![allow(unused_imports)]
@@ -44,55 +38,220 @@ use sp_std::marker::PhantomData;
 
 /// Weight functions needed for pallet_account_linker.
 pub trait WeightInfo {
-	fn link_eth() -> Weight;
-	fn link_btc() -> Weight;
+	fn link_eth(n: u32, ) -> Weight;
+	fn link_eth_typed(n: u32, ) -> Weight;
+	fn link_btc(n: u32, ) -> Weight;
 	fn link_polkadot() -> Weight;
-	fn accept_polkadot() -> Weight;
+	fn cancel_polkadot_link() -> Weight;
+	fn accept_polkadot(n: u32, ) -> Weight;
+	fn link_polkadot_signed(n: u32, ) -> Weight;
+	fn unlink_eth(n: u32, ) -> Weight;
+	fn unlink_btc(n: u32, ) -> Weight;
+	fn unlink_polkadot(n: u32, ) -> Weight;
+	fn link(n: u32, ) -> Weight;
+	fn unlink(n: u32, ) -> Weight;
+	fn link_btc_taproot(n: u32, ) -> Weight;
+	fn recover_eth() -> Weight;
+	fn recover_bip137() -> Weight;
+	fn recover_bip322() -> Weight;
+	fn recover_sol() -> Weight;
+	fn recover_cosmos() -> Weight;
+	fn recover_tron() -> Weight;
 }
 
 /// Weights for pallet_account_linker using the Substrate node and recommended hardware.
 pub struct SubstrateWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
-	fn link_eth() -> Weight {
+	fn link_eth(n: u32, ) -> Weight {
 		(324_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
 	}
-	fn link_btc() -> Weight {
+	fn link_eth_typed(n: u32, ) -> Weight {
+		(331_000_000 as Weight)
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	fn link_btc(n: u32, ) -> Weight {
 		(335_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
 	}
 	fn link_polkadot() -> Weight {
 		(335_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn cancel_polkadot_link() -> Weight {
+		(42_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
 	}
-	fn accept_polkadot() -> Weight {
+	fn accept_polkadot(n: u32, ) -> Weight {
 		(335_000_000 as Weight)
-		.saturating_add(T::DbWeight::get().reads(1 as Weight))
-		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	fn link_polkadot_signed(n: u32, ) -> Weight {
+		(342_000_000 as Weight)
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	fn unlink_eth(n: u32, ) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add((187_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn unlink_btc(n: u32, ) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add((187_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn unlink_polkadot(n: u32, ) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add((187_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn link(n: u32, ) -> Weight {
+		(66_000_000 as Weight)
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	fn unlink(n: u32, ) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add((187_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn link_btc_taproot(n: u32, ) -> Weight {
+		(1_120_000_000 as Weight)
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	fn recover_eth() -> Weight {
+		(258_000_000 as Weight)
+	}
+	fn recover_bip137() -> Weight {
+		(269_000_000 as Weight)
+	}
+	fn recover_bip322() -> Weight {
+		(1_054_000_000 as Weight)
+	}
+	fn recover_sol() -> Weight {
+		(76_000_000 as Weight)
+	}
+	fn recover_cosmos() -> Weight {
+		(230_000_000 as Weight)
+	}
+	fn recover_tron() -> Weight {
+		(226_000_000 as Weight)
 	}
 }
 
 // For backwards compatibility and tests
 impl WeightInfo for () {
-	fn link_eth() -> Weight {
+	fn link_eth(n: u32, ) -> Weight {
 		(324_000_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	fn link_eth_typed(n: u32, ) -> Weight {
+		(331_000_000 as Weight)
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
 	}
-	fn link_btc() -> Weight {
+	fn link_btc(n: u32, ) -> Weight {
 		(335_000_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
 	}
 	fn link_polkadot() -> Weight {
 		(335_000_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
 	}
-	fn accept_polkadot() -> Weight {
+	fn cancel_polkadot_link() -> Weight {
+		(42_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn accept_polkadot(n: u32, ) -> Weight {
 		(335_000_000 as Weight)
-		.saturating_add(RocksDbWeight::get().reads(1 as Weight))
-		.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+	fn link_polkadot_signed(n: u32, ) -> Weight {
+		(342_000_000 as Weight)
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	fn unlink_eth(n: u32, ) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add((187_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn unlink_btc(n: u32, ) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add((187_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn unlink_polkadot(n: u32, ) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add((187_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn link(n: u32, ) -> Weight {
+		(66_000_000 as Weight)
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	fn unlink(n: u32, ) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add((187_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn link_btc_taproot(n: u32, ) -> Weight {
+		(1_120_000_000 as Weight)
+			.saturating_add((214_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	fn recover_eth() -> Weight {
+		(258_000_000 as Weight)
+	}
+	fn recover_bip137() -> Weight {
+		(269_000_000 as Weight)
+	}
+	fn recover_bip322() -> Weight {
+		(1_054_000_000 as Weight)
+	}
+	fn recover_sol() -> Weight {
+		(76_000_000 as Weight)
+	}
+	fn recover_cosmos() -> Weight {
+		(230_000_000 as Weight)
+	}
+	fn recover_tron() -> Weight {
+		(226_000_000 as Weight)
 	}
 }
@@ -76,12 +76,13 @@ pub mod pallet {
 	use sp_runtime::offchain::{storage::StorageValueRef,};
 	use sp_runtime::traits::{AtLeast32BitUnsigned, Member, MaybeSerializeDeserialize,};
 	use weights::WeightInfo;
+	use account_linker::{ExternalAddress, LinkedAccountsProvider, OnLinkChanged};
 
 	type PositiveImbalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::PositiveImbalance;
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config + account_linker::Config + CreateSignedTransaction<Call<Self>> {
+	pub trait Config: frame_system::Config + CreateSignedTransaction<Call<Self>> {
 		type Balance: Parameter + Member + AtLeast32BitUnsigned + Codec + Default + Copy +
 			MaybeSerializeDeserialize;
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
@@ -95,6 +96,9 @@ pub mod pallet {
 		type Reward: OnUnbalanced<PositiveImbalanceOf<Self>>;
 		type OcwQueryReward: Get<<<Self as Config>::Currency as Currency<<Self as frame_system::Config>::AccountId>>::Balance>;
 		type WeightInfo: weights::WeightInfo;
+		/// The addresses linked to the claiming accounts. The runtime should also notify the pallet of
+		/// link changes by setting it as the `OnLinkChanged` of the account linker.
+		type LinkedAccounts: LinkedAccountsProvider<Self::AccountId>;
 	}
 
 	#[pallet::hooks]
@@ -572,7 +576,7 @@ pub mod pallet {
 						};
 
 						Self::fetch_balances(
-							<account_linker::Pallet<T>>::eth_addresses(account),
+							T::LinkedAccounts::eth_addresses(account),
 							urls::HttpRequest::GET(get),
 							&urls::parse_etherscan_balances).ok()
 					},
@@ -597,7 +601,7 @@ pub mod pallet {
 							api_token: token,
 						};
 						Self::fetch_balances(
-							<account_linker::Pallet<T>>::eth_addresses(account),
+							T::LinkedAccounts::eth_addresses(account),
 							urls::HttpRequest::POST(post),
 							&urls::parse_blockchain_info_balances).ok()
 					},
@@ -727,4 +731,16 @@ pub mod pallet {
 			}
 		}
 	}
+
+	// The balances of an account were aggregated from its previous links, so they are stale once
+	// the links change until the account claims again
+	impl<T: Config> OnLinkChanged<T::AccountId> for Pallet<T> {
+		fn on_linked(account: &T::AccountId, _address: &ExternalAddress<T::AccountId>) {
+			<AccountBalance<T>>::remove(account);
+		}
+
+		fn on_unlinked(account: &T::AccountId, _address: &ExternalAddress<T::AccountId>) {
+			<AccountBalance<T>>::remove(account);
+		}
+	}
 }
\ No newline at end of file
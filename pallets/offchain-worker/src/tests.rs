@@ -17,6 +17,7 @@
 
 use crate::*;
 use crate as offchain_worker;
+use account_linker::{ExternalAddress, LinkedAccountsProvider, OnLinkChanged};
 use frame_support::parameter_types;
 use sp_core::{ H256, sr25519::Signature,};
 use sp_runtime::{
@@ -39,7 +40,6 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
-		AccountLinker: account_linker::{Pallet, Call, Storage, Event<T>},
 		OffchainWorker: offchain_worker::{Pallet, Call, Storage, Event<T>,},
 	}
 );
@@ -119,9 +119,25 @@ impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for T
 	}
 }
 
-impl account_linker::Config for Test {
-	type Event = Event;
-	type WeightInfo = ();
+// No account has linked addresses
+pub struct LinkedAccounts;
+
+impl LinkedAccountsProvider<AccountId> for LinkedAccounts {
+	fn eth_addresses(_account: &AccountId) -> Vec<[u8; 20]> {
+		Vec::new()
+	}
+
+	fn btc_addresses(_account: &AccountId) -> Vec<Vec<u8>> {
+		Vec::new()
+	}
+
+	fn polkadot_addresses(_account: &AccountId) -> Vec<AccountId> {
+		Vec::new()
+	}
+
+	fn chain_addresses(_chain_id: account_linker::ChainId, _account: &AccountId) -> Vec<Vec<u8>> {
+		Vec::new()
+	}
 }
 
 parameter_types! {
@@ -141,6 +157,7 @@ impl Config for Test {
 	type Reward = ();
 	type OcwQueryReward = OcwQueryReward;
 	type WeightInfo = ();
+	type LinkedAccounts = LinkedAccounts;
 }
 
 
@@ -246,6 +263,23 @@ fn test_parse_infura_balances_2() {
 
 }
 
+#[test]
+fn test_link_changes_clear_balances() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let account = sp_core::sr25519::Public::from_raw([1u8; 32]);
+		let other = sp_core::sr25519::Public::from_raw([2u8; 32]);
+		AccountBalance::<Test>::insert(&account, (Some(1), Some(2)));
+		AccountBalance::<Test>::insert(&other, (Some(3), Some(4)));
+
+		<OffchainWorker as OnLinkChanged<AccountId>>::on_linked(&account, &ExternalAddress::Eth([1u8; 20]));
+		assert_eq!(OffchainWorker::account_balance(&account), (None, None));
+		assert_eq!(OffchainWorker::account_balance(&other), (Some(3), Some(4)));
+
+		<OffchainWorker as OnLinkChanged<AccountId>>::on_unlinked(&other, &ExternalAddress::Btc(b"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_vec()));
+		assert_eq!(OffchainWorker::account_balance(&other), (None, None));
+	});
+}
+
 // fetch_balances only executed in offchain worker context, need investigate how to call it in test
 // #[test]
 // fn test_fetch_balances() {	